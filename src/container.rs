@@ -1,12 +1,18 @@
+use crate::hash::{FxHashMap, FxHashMapBuilder};
+use std::fmt;
+use std::hash::Hash;
+use std::mem::MaybeUninit;
+
+/// Stack with fixed capacity that lives on the stack.
 pub struct StaticStack<T, const CAPACITY: usize> {
-    data: [T; CAPACITY],
-    ptr: usize,
+    data: [MaybeUninit<T>; CAPACITY],
+    ptr: usize, // data[..ptr] is initialized
 }
 
-impl<T: Copy + Default, const CAPACITY: usize> StaticStack<T, CAPACITY> {
-    pub fn new() -> Self {
+impl<T, const CAPACITY: usize> StaticStack<T, CAPACITY> {
+    pub const fn new() -> Self {
         StaticStack {
-            data: [T::default(); CAPACITY],
+            data: [const { MaybeUninit::uninit() }; CAPACITY],
             ptr: 0,
         }
     }
@@ -20,24 +26,492 @@ impl<T: Copy + Default, const CAPACITY: usize> StaticStack<T, CAPACITY> {
     }
 
     pub fn push(&mut self, element: T) {
-        if self.ptr >= CAPACITY {
+        if self.try_push(element).is_err() {
             panic!("Out of stack");
         }
-        self.data[self.ptr] = element;
+    }
+
+    /// Push element, or give it back if the stack is full.
+    pub fn try_push(&mut self, element: T) -> Result<(), T> {
+        if self.ptr >= CAPACITY {
+            return Err(element);
+        }
+        self.data[self.ptr].write(element);
         self.ptr += 1;
+        Ok(())
     }
 
     pub fn pop(&mut self) -> Option<T> {
         if self.is_empty() {
             return None;
         }
-        let ret = self.data[self.ptr - 1];
         self.ptr -= 1;
+        // SAFETY: the element was initialized and is no longer tracked by ptr
+        Some(unsafe { self.data[self.ptr].assume_init_read() })
+    }
+
+    /// Top of the stack.
+    pub fn peek(&self) -> Option<&T> {
+        self.as_slice().last()
+    }
+
+    /// Remove all elements.
+    pub fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Elements from bottom to top.
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: data[..ptr] is initialized, and MaybeUninit<T> has the layout of T
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const T, self.ptr) }
+    }
+
+    /// Iterate from bottom to top.
+    pub fn iter(&self) -> std::slice::Iter<'_, T> {
+        self.as_slice().iter()
+    }
+}
+
+impl<T, const CAPACITY: usize> Default for StaticStack<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const CAPACITY: usize> Drop for StaticStack<T, CAPACITY> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const CAPACITY: usize> Extend<T> for StaticStack<T, CAPACITY> {
+    /// Push all elements, panics if the stack runs full.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|t| self.push(t));
+    }
+}
+
+impl<T: fmt::Debug, const CAPACITY: usize> fmt::Debug for StaticStack<T, CAPACITY> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// FIFO queue with fixed capacity that lives on the stack.
+pub struct StaticQueue<T, const CAPACITY: usize> {
+    data: [MaybeUninit<T>; CAPACITY],
+    head: usize,
+    len: usize, // data[head..head + len] (wrapping) is initialized
+}
+
+impl<T, const CAPACITY: usize> StaticQueue<T, CAPACITY> {
+    pub const fn new() -> Self {
+        StaticQueue {
+            data: [const { MaybeUninit::uninit() }; CAPACITY],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn push_back(&mut self, element: T) {
+        if self.try_push_back(element).is_err() {
+            panic!("Out of queue");
+        }
+    }
+
+    /// Push element, or give it back if the queue is full.
+    pub fn try_push_back(&mut self, element: T) -> Result<(), T> {
+        if self.len >= CAPACITY {
+            return Err(element);
+        }
+        self.data[(self.head + self.len) % CAPACITY].write(element);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        // SAFETY: head is initialized and no longer tracked after moving head
+        let ret = unsafe { self.data[self.head].assume_init_read() };
+        self.head = (self.head + 1) % CAPACITY;
+        self.len -= 1;
+        Some(ret)
+    }
+
+    /// Next element to be popped.
+    pub fn front(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    /// Remove all elements.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Iterate from front to back.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        (0..self.len).map(|i| {
+            // SAFETY: the first len slots from head are initialized
+            unsafe { self.data[(self.head + i) % CAPACITY].assume_init_ref() }
+        })
+    }
+}
+
+impl<T, const CAPACITY: usize> Default for StaticQueue<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const CAPACITY: usize> Drop for StaticQueue<T, CAPACITY> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Debug, const CAPACITY: usize> fmt::Debug for StaticQueue<T, CAPACITY> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Growable FIFO ring buffer.
+pub struct RingBuffer<T> {
+    data: Vec<MaybeUninit<T>>,
+    head: usize,
+    len: usize, // data[head..head + len] (wrapping) is initialized
+}
+
+impl<T> RingBuffer<T> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        RingBuffer {
+            data: (0..capacity).map(|_| MaybeUninit::uninit()).collect(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Make room for at least additional more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.len + additional;
+        if needed <= self.capacity() {
+            return;
+        }
+        let capacity = needed.max(2 * self.capacity());
+        // unroll into a fresh buffer that starts at index 0
+        let mut data = Vec::with_capacity(capacity);
+        let n = self.capacity();
+        data.extend((0..self.len).map(|i| {
+            // SAFETY: the first len slots from head are initialized, and each is
+            // moved out once before the old buffer is replaced without dropping them
+            MaybeUninit::new(unsafe { self.data[(self.head + i) % n].assume_init_read() })
+        }));
+        data.resize_with(capacity, MaybeUninit::uninit);
+        self.data = data;
+        self.head = 0;
+    }
+
+    pub fn push_back(&mut self, element: T) {
+        self.reserve(1);
+        let n = self.capacity();
+        self.data[(self.head + self.len) % n].write(element);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        // SAFETY: head is initialized and no longer tracked after moving head
+        let ret = unsafe { self.data[self.head].assume_init_read() };
+        self.head = (self.head + 1) % self.capacity();
+        self.len -= 1;
         Some(ret)
     }
+
+    /// Next element to be popped.
+    pub fn front(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    /// Remove all elements, keeping the capacity.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+        self.head = 0;
+    }
+
+    /// Iterate from front to back.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        let n = self.capacity();
+        (0..self.len).map(move |i| {
+            // SAFETY: the first len slots from head are initialized
+            unsafe { self.data[(self.head + i) % n].assume_init_ref() }
+        })
+    }
+}
+
+impl<T: Clone> Clone for RingBuffer<T> {
+    fn clone(&self) -> Self {
+        let mut ret = Self::with_capacity(self.len);
+        ret.extend(self.iter().cloned());
+        ret
+    }
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
 }
 
-impl<T: Copy + Default, const CAPACITY: usize> Default for StaticStack<T, CAPACITY> {
+impl<T> Default for RingBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for RingBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        iter.for_each(|t| self.push_back(t));
+    }
+}
+
+/// Disjoint-set forest (union-find) over the elements 0..n.
+///
+/// Uses path compression and union by rank, so operations take nearly constant
+/// amortized time.
+///
+/// https://en.wikipedia.org/wiki/Disjoint-set_data_structure
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+    size: Vec<usize>,
+    num_components: usize,
+}
+
+impl DisjointSet {
+    /// Create n singleton sets.
+    pub fn new(n: usize) -> Self {
+        DisjointSet {
+            parent: (0..n).collect(),
+            rank: vec![0; n],
+            size: vec![1; n],
+            num_components: n,
+        }
+    }
+
+    /// Number of elements.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// Add a new singleton set and return its element.
+    pub fn push(&mut self) -> usize {
+        let x = self.parent.len();
+        self.parent.push(x);
+        self.rank.push(0);
+        self.size.push(1);
+        self.num_components += 1;
+        x
+    }
+
+    /// Representative of the set that contains x.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // path compression
+        let mut x = x;
+        while self.parent[x] != root {
+            x = std::mem::replace(&mut self.parent[x], root);
+        }
+        root
+    }
+
+    /// Merge the sets containing x and y.
+    ///
+    /// Returns false if they were already in the same set.
+    pub fn union(&mut self, x: usize, y: usize) -> bool {
+        let (mut x, mut y) = (self.find(x), self.find(y));
+        if x == y {
+            return false;
+        }
+        if self.rank[x] < self.rank[y] {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.parent[y] = x;
+        self.size[x] += self.size[y];
+        if self.rank[x] == self.rank[y] {
+            self.rank[x] += 1;
+        }
+        self.num_components -= 1;
+        true
+    }
+
+    /// Check if x and y are in the same set.
+    pub fn same(&mut self, x: usize, y: usize) -> bool {
+        self.find(x) == self.find(y)
+    }
+
+    /// Size of the set containing x.
+    pub fn size_of(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// Number of disjoint sets.
+    pub fn num_components(&self) -> usize {
+        self.num_components
+    }
+
+    /// Representatives of all sets.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|x| self.parent[*x] == *x)
+    }
+
+    /// All sets, as lists of elements in increasing order.
+    pub fn components(&mut self) -> Vec<Vec<usize>> {
+        let mut idx = FxHashMap::new();
+        let mut ret: Vec<Vec<usize>> = vec![];
+        for x in 0..self.len() {
+            let root = self.find(x);
+            let i = *idx.entry(root).or_insert_with(|| {
+                ret.push(vec![]);
+                ret.len() - 1
+            });
+            ret[i].push(x);
+        }
+        ret
+    }
+}
+
+/// Disjoint-set forest over arbitrary keys, e.g. Vec2i or string ids.
+///
+/// Keys are added on first use.
+#[derive(Debug, Clone)]
+pub struct KeyedDisjointSet<K> {
+    ids: FxHashMap<K, usize>,
+    keys: Vec<K>,
+    set: DisjointSet,
+}
+
+impl<K: Hash + Eq + Clone> KeyedDisjointSet<K> {
+    pub fn new() -> Self {
+        KeyedDisjointSet {
+            ids: FxHashMap::new(),
+            keys: vec![],
+            set: DisjointSet::new(0),
+        }
+    }
+
+    /// Number of keys.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, k: &K) -> bool {
+        self.ids.contains_key(k)
+    }
+
+    /// Add key as a singleton set if it is new, and return its index.
+    pub fn insert(&mut self, k: &K) -> usize {
+        if let Some(id) = self.ids.get(k) {
+            return *id;
+        }
+        let id = self.set.push();
+        self.ids.insert(k.clone(), id);
+        self.keys.push(k.clone());
+        id
+    }
+
+    /// Representative key of the set containing k, None for unknown keys.
+    pub fn find(&mut self, k: &K) -> Option<&K> {
+        let id = *self.ids.get(k)?;
+        let root = self.set.find(id);
+        Some(&self.keys[root])
+    }
+
+    /// Merge the sets containing a and b, adding the keys if needed.
+    ///
+    /// Returns false if they were already in the same set.
+    pub fn union(&mut self, a: &K, b: &K) -> bool {
+        let (a, b) = (self.insert(a), self.insert(b));
+        self.set.union(a, b)
+    }
+
+    /// Check if a and b are known and in the same set.
+    pub fn same(&mut self, a: &K, b: &K) -> bool {
+        match (self.ids.get(a), self.ids.get(b)) {
+            (Some(a), Some(b)) => self.set.same(*a, *b),
+            _ => false,
+        }
+    }
+
+    /// Size of the set containing k (0 for unknown keys).
+    pub fn size_of(&mut self, k: &K) -> usize {
+        self.ids.get(k).map_or(0, |id| self.set.size_of(*id))
+    }
+
+    /// Number of disjoint sets.
+    pub fn num_components(&self) -> usize {
+        self.set.num_components()
+    }
+
+    /// All sets, as lists of keys in insertion order.
+    pub fn components(&mut self) -> Vec<Vec<K>> {
+        self.set
+            .components()
+            .into_iter()
+            .map(|c| c.into_iter().map(|id| self.keys[id].clone()).collect())
+            .collect()
+    }
+}
+
+impl<K: Hash + Eq + Clone> Default for KeyedDisjointSet<K> {
     fn default() -> Self {
         Self::new()
     }
@@ -46,17 +520,24 @@ impl<T: Copy + Default, const CAPACITY: usize> Default for StaticStack<T, CAPACI
 /// Implementation of the Priority Queue concept for the special case of integer-valued
 /// costs with bounded increases.
 ///
+/// Elements beyond the bucket window go to an overflow list and are moved into the
+/// buckets once the window reaches them, so large increments work but are slower.
+///
 /// https://en.wikipedia.org/wiki/Bucket_queue
 pub struct BucketQueue<T> {
     buckets: Vec<Vec<T>>,
     priority: u64,
+    len: usize,
+    overflow: Vec<(u64, T)>,
+    overflow_min: u64,
 }
 
 impl<T> BucketQueue<T> {
     /// Create a new BucketQueue.
     ///
     /// The number of buckets num_buckets bounds the largest
-    /// priority increment between current node and newly inserted node.
+    /// priority increment between current node and newly inserted node
+    /// that is handled without the overflow list.
     ///
     /// In particular, a bucket queue with N buckets allows cost increments in [0, N-1).
     pub fn new(num_buckets: usize) -> Self
@@ -68,17 +549,20 @@ impl<T> BucketQueue<T> {
         BucketQueue {
             buckets,
             priority: 0,
+            len: 0,
+            overflow: vec![],
+            overflow_min: u64::MAX,
         }
     }
 
     /// Check if queue is empty.
     pub fn is_empty(&self) -> bool {
-        self.buckets.iter().all(|x| x.is_empty())
+        self.len == 0
     }
 
-    /// Return length of bucket.
+    /// Return number of elements.
     pub fn len(&self) -> usize {
-        self.buckets.iter().map(|x| x.len()).sum()
+        self.len
     }
 
     /// Remove all elements and reset priority counter to 0.
@@ -86,18 +570,27 @@ impl<T> BucketQueue<T> {
         for bucket in self.buckets.iter_mut() {
             bucket.clear();
         }
+        self.overflow.clear();
+        self.overflow_min = u64::MAX;
         self.priority = 0;
+        self.len = 0;
     }
 
     /// Push a new priority-val pair to the queue.
     ///
-    /// The priority must be s.t. internal_priority <= priority < internal_priority + n,
-    /// where n is the number of buckets.
+    /// The priority must be at least the internal priority. Priorities of
+    /// internal_priority + n or more, where n is the number of buckets, go to the
+    /// overflow list.
     pub fn push(&mut self, priority: u64, val: T) {
         assert!(self.priority <= priority);
-        assert!(priority < self.priority + self.buckets.len() as u64);
-        let bucket_id = priority as usize % self.buckets.len();
-        self.buckets[bucket_id].push(val);
+        self.len += 1;
+        if priority < self.priority + self.buckets.len() as u64 {
+            let bucket_id = priority as usize % self.buckets.len();
+            self.buckets[bucket_id].push(val);
+        } else {
+            self.overflow_min = self.overflow_min.min(priority);
+            self.overflow.push((priority, val));
+        }
     }
 
     /// Return and return an element with the lowest priority.
@@ -108,20 +601,218 @@ impl<T> BucketQueue<T> {
     /// Future elements must have a priority at least as large as the most
     /// recently returned priority.
     pub fn pop(&mut self) -> Option<(u64, T)> {
-        for _ in 0..self.buckets.len() {
-            if !self.buckets[self.priority as usize % self.buckets.len()].is_empty() {
-                break;
+        let bucket_id = self.advance()?;
+        self.len -= 1;
+        self.buckets[bucket_id].pop().map(|x| (self.priority, x))
+    }
+
+    /// Return an element with the lowest priority without removing it.
+    ///
+    /// Like pop, this advances the internal priority counter to the lowest priority.
+    pub fn peek(&mut self) -> Option<(u64, &T)> {
+        let bucket_id = self.advance()?;
+        self.buckets[bucket_id].last().map(|x| (self.priority, x))
+    }
+
+    /// Advance internal priority to the lowest element and return its bucket.
+    fn advance(&mut self) -> Option<usize> {
+        if self.len == 0 {
+            return None;
+        }
+        let n = self.buckets.len();
+        if self.len == self.overflow.len() {
+            // buckets are empty, jump straight to the overflow
+            self.priority = self.overflow_min;
+        }
+        loop {
+            if self.priority == self.overflow_min {
+                self.refill();
+            }
+            let bucket_id = self.priority as usize % n;
+            if !self.buckets[bucket_id].is_empty() {
+                return Some(bucket_id);
             }
             self.priority += 1;
         }
-        let bucket_id = self.priority as usize % self.buckets.len();
-        self.buckets[bucket_id].pop().map(|x| (self.priority, x))
+    }
+
+    /// Move overflow elements that are inside the current window to the buckets.
+    fn refill(&mut self) {
+        let n = self.buckets.len();
+        let end = self.priority + n as u64;
+        let mut rest = vec![];
+        for (priority, val) in self.overflow.drain(..) {
+            if priority < end {
+                self.buckets[priority as usize % n].push(val);
+            } else {
+                rest.push((priority, val));
+            }
+        }
+        self.overflow_min = rest.iter().map(|(p, _)| *p).min().unwrap_or(u64::MAX);
+        self.overflow = rest;
+    }
+}
+
+/// Radix heap: a priority queue for monotone integer priorities.
+///
+/// Like a BucketQueue, pushed priorities must be at least the last popped priority,
+/// but there is no bound on the increments. Elements are kept in 65 buckets by the
+/// highest bit in which their priority differs from the last popped one.
+///
+/// https://en.wikipedia.org/wiki/Radix_heap
+pub struct RadixHeap<T> {
+    buckets: Vec<Vec<(u64, T)>>,
+    last: u64,
+    len: usize,
+}
+
+impl<T> RadixHeap<T> {
+    pub fn new() -> Self {
+        RadixHeap {
+            buckets: (0..65).map(|_| vec![]).collect(),
+            last: 0,
+            len: 0,
+        }
+    }
+
+    /// Check if queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return number of elements.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Remove all elements and reset priority counter to 0.
+    pub fn reset(&mut self) {
+        for bucket in self.buckets.iter_mut() {
+            bucket.clear();
+        }
+        self.last = 0;
+        self.len = 0;
+    }
+
+    fn bucket_of(&self, priority: u64) -> usize {
+        64 - (priority ^ self.last).leading_zeros() as usize
+    }
+
+    /// Push a new priority-val pair to the queue.
+    ///
+    /// The priority must be at least the most recently popped priority.
+    pub fn push(&mut self, priority: u64, val: T) {
+        assert!(self.last <= priority);
+        let bucket_id = self.bucket_of(priority);
+        self.buckets[bucket_id].push((priority, val));
+        self.len += 1;
+    }
+
+    /// Remove and return an element with the lowest priority.
+    pub fn pop(&mut self) -> Option<(u64, T)> {
+        self.advance()?;
+        self.len -= 1;
+        self.buckets[0].pop()
+    }
+
+    /// Return an element with the lowest priority without removing it.
+    ///
+    /// Like pop, this sets the lower bound for future priorities to the lowest priority.
+    pub fn peek(&mut self) -> Option<(u64, &T)> {
+        self.advance()?;
+        self.buckets[0].last().map(|(p, x)| (*p, x))
+    }
+
+    /// Make sure bucket 0 holds the elements with the lowest priority.
+    fn advance(&mut self) -> Option<()> {
+        if self.buckets[0].is_empty() {
+            let i = self.buckets.iter().position(|b| !b.is_empty())?;
+            let bucket = std::mem::take(&mut self.buckets[i]);
+            self.last = bucket.iter().map(|(p, _)| *p).min().unwrap();
+            // all elements move to a lower bucket, since they agree with last on bit i - 1
+            for (p, x) in bucket {
+                let bucket_id = self.bucket_of(p);
+                self.buckets[bucket_id].push((p, x));
+            }
+        }
+        Some(())
+    }
+}
+
+impl<T> Default for RadixHeap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Priority queue for monotone integer priorities, chosen by the largest edge weight.
+///
+/// Small weights use a BucketQueue with one bucket per possible increment, large
+/// weights use a RadixHeap.
+pub enum MonotoneQueue<T> {
+    Bucket(BucketQueue<T>),
+    Radix(RadixHeap<T>),
+}
+
+impl<T: Clone> MonotoneQueue<T> {
+    /// Largest edge weight for which a BucketQueue is used.
+    pub const MAX_BUCKET_WEIGHT: u64 = 1 << 16;
+
+    /// Create queue for searches where priorities increase by at most max_weight per step.
+    pub fn for_max_weight(max_weight: u64) -> Self {
+        if max_weight <= Self::MAX_BUCKET_WEIGHT {
+            MonotoneQueue::Bucket(BucketQueue::new(max_weight as usize + 1))
+        } else {
+            MonotoneQueue::Radix(RadixHeap::new())
+        }
+    }
+}
+
+impl<T> MonotoneQueue<T> {
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            MonotoneQueue::Bucket(q) => q.len(),
+            MonotoneQueue::Radix(q) => q.len(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        match self {
+            MonotoneQueue::Bucket(q) => q.reset(),
+            MonotoneQueue::Radix(q) => q.reset(),
+        }
+    }
+
+    pub fn push(&mut self, priority: u64, val: T) {
+        match self {
+            MonotoneQueue::Bucket(q) => q.push(priority, val),
+            MonotoneQueue::Radix(q) => q.push(priority, val),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<(u64, T)> {
+        match self {
+            MonotoneQueue::Bucket(q) => q.pop(),
+            MonotoneQueue::Radix(q) => q.pop(),
+        }
+    }
+
+    pub fn peek(&mut self) -> Option<(u64, &T)> {
+        match self {
+            MonotoneQueue::Bucket(q) => q.peek(),
+            MonotoneQueue::Radix(q) => q.peek(),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::Lcg;
 
     #[test]
     fn test_stack() {
@@ -137,6 +828,89 @@ mod tests {
         assert_eq!(stack.pop(), Some(1));
     }
 
+    #[test]
+    fn test_stack_ops() {
+        // no Copy or Default needed
+        let mut stack = StaticStack::<String, 3>::new();
+        stack.extend(["a".to_string(), "b".to_string()]);
+        assert_eq!(stack.peek(), Some(&"b".to_string()));
+        assert_eq!(stack.iter().cloned().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(stack.try_push("c".to_string()), Ok(()));
+        assert_eq!(stack.try_push("d".to_string()), Err("d".to_string()));
+        assert_eq!(format!("{:?}", stack), r#"["a", "b", "c"]"#);
+        stack.clear();
+        assert!(stack.is_empty());
+        assert_eq!(stack.peek(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Out of stack")]
+    fn test_stack_overflow() {
+        let mut stack = StaticStack::<u8, 2>::new();
+        stack.extend([1, 2, 3]);
+    }
+
+    #[test]
+    fn test_static_queue() {
+        let mut queue = StaticQueue::<String, 3>::new();
+        assert_eq!(queue.pop_front(), None);
+        // push and pop across the wrap-around point
+        for i in 0..10 {
+            queue.push_back(i.to_string());
+            queue.push_back((i + 100).to_string());
+            assert_eq!(queue.pop_front(), Some(i.to_string()));
+            assert_eq!(queue.front(), Some(&(i + 100).to_string()));
+            assert_eq!(queue.pop_front(), Some((i + 100).to_string()));
+        }
+        queue.push_back("x".to_string());
+        queue.push_back("y".to_string());
+        queue.push_back("z".to_string());
+        assert_eq!(queue.try_push_back("w".to_string()), Err("w".to_string()));
+        assert_eq!(
+            queue.iter().cloned().collect::<Vec<_>>(),
+            vec!["x", "y", "z"]
+        );
+        assert_eq!(queue.len(), 3);
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let mut queue = RingBuffer::with_capacity(2);
+        queue.push_back(0);
+        queue.push_back(1);
+        assert_eq!(queue.pop_front(), Some(0));
+        // grows while wrapped around
+        queue.extend(2..6);
+        assert!(queue.capacity() >= 5);
+        assert_eq!(
+            queue.iter().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5]
+        );
+        let mut popped = vec![];
+        while let Some(x) = queue.pop_front() {
+            popped.push(x);
+            if x < 3 {
+                queue.push_back(10 + x);
+            }
+        }
+        assert_eq!(popped, vec![1, 2, 3, 4, 5, 11, 12]);
+        queue.reserve(100);
+        assert!(queue.capacity() >= 100);
+        assert!(queue.is_empty());
+
+        // elements left in the buffer are dropped exactly once, also after growing
+        let rc = std::rc::Rc::new(());
+        let mut queue = RingBuffer::with_capacity(3);
+        queue.extend([rc.clone(), rc.clone()]);
+        queue.pop_front();
+        queue.extend([rc.clone(), rc.clone(), rc.clone()]);
+        let copy = queue.clone();
+        assert_eq!(std::rc::Rc::strong_count(&rc), 9);
+        drop(queue);
+        drop(copy);
+        assert_eq!(std::rc::Rc::strong_count(&rc), 1);
+    }
+
     #[test]
     fn test_bucket_queue() {
         let mut queue = BucketQueue::<()>::new(5);
@@ -180,4 +954,101 @@ mod tests {
 
         assert!(queue.is_empty());
     }
+
+    #[test]
+    fn test_bucket_queue_overflow() {
+        let mut queue = BucketQueue::<u64>::new(3);
+        queue.push(0, 0);
+        queue.push(10, 10);
+        queue.push(2, 2);
+        queue.push(7, 7);
+        assert_eq!(queue.len(), 4);
+        assert_eq!(queue.pop(), Some((0, 0)));
+        assert_eq!(queue.peek(), Some((2, &2)));
+        assert_eq!(queue.pop(), Some((2, 2)));
+        queue.push(8, 8);
+        assert_eq!(queue.pop(), Some((7, 7)));
+        assert_eq!(queue.pop(), Some((8, 8)));
+        assert_eq!(queue.pop(), Some((10, 10)));
+        assert_eq!(queue.pop(), None);
+        assert!(queue.is_empty());
+    }
+
+    /// Push and pop pseudo-random monotone priorities, checking against a sorted list.
+    fn check_monotone_queue(mut queue: MonotoneQueue<u64>, max_step: u64) {
+        let mut expected: Vec<u64> = vec![];
+        let mut rng = Lcg::new(11);
+        let mut last = 0;
+        for i in 0..3000 {
+            let seed = rng.next_u64();
+            if i < 2500 && (seed >> 60) < 10 {
+                let p = last + (seed >> 20) % (max_step + 1);
+                queue.push(p, p);
+                expected.push(p);
+                expected.sort_by(|a, b| b.cmp(a));
+            } else {
+                let popped = queue.pop();
+                assert_eq!(popped.map(|(p, _)| p), expected.pop());
+                if let Some((p, x)) = popped {
+                    assert_eq!(p, x);
+                    last = p;
+                }
+            }
+            assert_eq!(queue.len(), expected.len());
+        }
+    }
+
+    #[test]
+    fn test_monotone_queues() {
+        let bucket = MonotoneQueue::for_max_weight(100);
+        assert!(matches!(bucket, MonotoneQueue::Bucket(_)));
+        check_monotone_queue(bucket, 100);
+
+        let radix = MonotoneQueue::for_max_weight(1 << 40);
+        assert!(matches!(radix, MonotoneQueue::Radix(_)));
+        check_monotone_queue(radix, 1 << 40);
+
+        // increments beyond the bucket window go through the overflow list
+        check_monotone_queue(MonotoneQueue::Bucket(BucketQueue::new(8)), 100);
+    }
+
+    #[test]
+    fn test_disjoint_set() {
+        let mut set = DisjointSet::new(6);
+        assert_eq!(set.num_components(), 6);
+        assert!(set.union(0, 1));
+        assert!(set.union(2, 3));
+        assert!(set.union(1, 3));
+        assert!(!set.union(0, 2));
+        assert_eq!(set.num_components(), 3);
+        assert!(set.same(0, 3));
+        assert!(!set.same(0, 4));
+        assert_eq!(set.size_of(2), 4);
+        assert_eq!(set.size_of(5), 1);
+        let x = set.push();
+        set.union(x, 5);
+        assert_eq!(set.roots().count(), 3);
+        assert_eq!(
+            set.components(),
+            vec![vec![0, 1, 2, 3], vec![4], vec![5, 6]]
+        );
+    }
+
+    #[test]
+    fn test_keyed_disjoint_set() {
+        let mut set = KeyedDisjointSet::new();
+        for (a, b) in [("kh", "tc"), ("qp", "kh"), ("de", "cg"), ("ka", "co")] {
+            set.union(&a, &b);
+        }
+        set.insert(&"yn");
+        assert_eq!(set.len(), 8);
+        assert_eq!(set.num_components(), 4);
+        assert!(set.same(&"qp", &"tc"));
+        assert!(!set.same(&"qp", &"de"));
+        assert!(!set.same(&"qp", &"xx"));
+        assert_eq!(set.size_of(&"tc"), 3);
+        assert_eq!(set.size_of(&"xx"), 0);
+        assert_eq!(set.find(&"xx"), None);
+        assert_eq!(set.components()[0], vec!["kh", "tc", "qp"]);
+    }
 }
@@ -0,0 +1,213 @@
+use crate::container::RingBuffer;
+use crate::heap::MinHeap;
+use crate::map2d::Map;
+use crate::vec2::Vec2i;
+
+/// Distance metric for distance fields.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Metric {
+    /// Shortest path with orthogonal steps through passable cells.
+    Bfs,
+    /// Shortest path with orthogonal and diagonal steps through passable cells.
+    BfsDiagonal,
+    /// Manhattan distance, ignoring impassable cells.
+    Manhattan,
+    /// Chebyshev distance, ignoring impassable cells.
+    Chebyshev,
+}
+
+/// Distance to, and index of, the nearest source for every cell.
+///
+/// Cells that are impassable or unreachable are None.
+#[derive(Debug, Clone)]
+pub struct DistanceField {
+    pub dist: Map<Option<u32>>,
+    pub nearest: Map<Option<usize>>,
+}
+
+impl DistanceField {
+    fn new<T>(map: &Map<T>) -> Self {
+        DistanceField {
+            dist: map.same_size_with(None),
+            nearest: map.same_size_with(None),
+        }
+    }
+}
+
+/// Compute distances from many sources.
+///
+/// Ties between sources are broken in favor of the source that comes first.
+/// Sources outside of the map are ignored.
+pub fn distance_field<T, F>(
+    map: &Map<T>,
+    sources: &[Vec2i],
+    metric: Metric,
+    passable: F,
+) -> DistanceField
+where
+    F: Fn(&T) -> bool,
+{
+    let mut ret = DistanceField::new(map);
+    let mut queue = RingBuffer::new();
+    let mut visited = map.same_size_with(false);
+
+    let blocking = matches!(metric, Metric::Bfs | Metric::BfsDiagonal);
+    let diagonal = matches!(metric, Metric::BfsDiagonal | Metric::Chebyshev);
+
+    for (i, s) in sources.iter().enumerate() {
+        if let Some(s) = map.resolve(s) {
+            if (!blocking || passable(&map[&s])) && !visited[&s] {
+                visited[&s] = true;
+                queue.push_back((s, i, 0));
+            }
+        }
+    }
+
+    while let Some((cur, i, d)) = queue.pop_front() {
+        if passable(&map[&cur]) {
+            ret.dist[&cur] = Some(d);
+            ret.nearest[&cur] = Some(i);
+        }
+        let mut expand = |next: Vec2i| {
+            if !visited[&next] && (!blocking || passable(&map[&next])) {
+                visited[&next] = true;
+                queue.push_back((next, i, d + 1));
+            }
+        };
+        if diagonal {
+            map.iter_neighbors8(&cur).for_each(&mut expand);
+        } else {
+            map.iter_neighbors4(&cur).for_each(&mut expand);
+        }
+    }
+
+    ret
+}
+
+/// Compute distances from many sources when entering a cell has a cost.
+///
+/// `cost` returns the cost of stepping into a cell, or None if the cell is impassable.
+/// Sources have distance 0 and moves are orthogonal.
+pub fn weighted_distance_field<T, F>(map: &Map<T>, sources: &[Vec2i], cost: F) -> DistanceField
+where
+    F: Fn(&T) -> Option<u32>,
+{
+    let mut ret = DistanceField::new(map);
+    let mut heap = MinHeap::new(|a: &(u32, usize, Vec2i), b: &(u32, usize, Vec2i)| {
+        (a.0, a.1).cmp(&(b.0, b.1))
+    });
+
+    for (i, s) in sources.iter().enumerate() {
+        if let Some(s) = map.resolve(s) {
+            if cost(&map[&s]).is_some() {
+                heap.push((0, i, s));
+            }
+        }
+    }
+
+    while let Some((d, i, cur)) = heap.pop() {
+        if ret.dist[&cur].is_some() {
+            continue; // already settled
+        }
+        ret.dist[&cur] = Some(d);
+        ret.nearest[&cur] = Some(i);
+        for next in map.iter_neighbors4(&cur) {
+            if ret.dist[&next].is_none() {
+                if let Some(c) = cost(&map[&next]) {
+                    heap.push((d + c, i, next));
+                }
+            }
+        }
+    }
+
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAP: &str = "\
+.....
+.###.
+.....
+#....";
+
+    fn render(field: &Map<Option<u32>>) -> String {
+        field
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|d| d.map(|d| char::from_digit(d, 36).unwrap()).unwrap_or('#'))
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_bfs() {
+        let map: Map<char> = MAP.parse().unwrap();
+        let field = distance_field(&map, &[Vec2i::new(0, 0)], Metric::Bfs, |c| *c == '.');
+        assert_eq!(render(&field.dist), "01234\n1###5\n23456\n#4567");
+    }
+
+    #[test]
+    fn test_bfs_diagonal() {
+        let map: Map<char> = MAP.parse().unwrap();
+        let field = distance_field(&map, &[Vec2i::new(0, 0)], Metric::BfsDiagonal, |c| {
+            *c == '.'
+        });
+        assert_eq!(render(&field.dist), "01234\n1###4\n22345\n#3345");
+    }
+
+    #[test]
+    fn test_geometric() {
+        let map: Map<char> = MAP.parse().unwrap();
+        let source = [Vec2i::new(2, 1)];
+        let field = distance_field(&map, &source, Metric::Manhattan, |c| *c == '.');
+        assert_eq!(render(&field.dist), "32123\n2###2\n32123\n#3234");
+        for (p, d) in field.dist.iter() {
+            if let Some(d) = d {
+                assert_eq!(*d as u64, p.manhattan_dist(&source[0]));
+            }
+        }
+
+        let field = distance_field(&map, &source, Metric::Chebyshev, |c| *c == '.');
+        assert_eq!(render(&field.dist), "21112\n2###2\n21112\n#2222");
+    }
+
+    #[test]
+    fn test_voronoi() {
+        let map: Map<char> = MAP.parse().unwrap();
+        let sources = [Vec2i::new(0, 0), Vec2i::new(4, 0), Vec2i::new(1, 1)];
+        let field = distance_field(&map, &sources, Metric::Bfs, |c| *c == '.');
+        assert_eq!(field.nearest[(2, 0)], Some(0));
+        assert_eq!(field.nearest[(3, 4)], Some(1));
+        assert_eq!(field.dist[(3, 4)], Some(3));
+        assert_eq!(field.nearest[(3, 0)], None);
+        assert_eq!(field.dist[(1, 1)], None); // blocked source is ignored
+        assert_eq!(field.dist[(0, 2)], Some(2));
+        assert_eq!(field.nearest[(0, 2)], Some(0)); // tie broken by source order
+    }
+
+    #[test]
+    fn test_weighted() {
+        let map: Map<char> = "1191\n1191\n1111".parse().unwrap();
+        let field = weighted_distance_field(&map, &[Vec2i::new(0, 0)], |c| c.to_digit(10));
+        assert_eq!(field.dist[(0, 3)], Some(7));
+        assert_eq!(field.dist[(0, 2)], Some(10));
+
+        let unweighted = weighted_distance_field(&map, &[Vec2i::new(0, 0)], |_| Some(1));
+        let bfs = distance_field(&map, &[Vec2i::new(0, 0)], Metric::Bfs, |_| true);
+        assert_eq!(render(&unweighted.dist), render(&bfs.dist));
+    }
+
+    #[test]
+    fn test_toroidal() {
+        let map: Map<char> = ".....".parse().unwrap();
+        let map = map.toroidal();
+        let field = distance_field(&map, &[Vec2i::new(0, 0)], Metric::Bfs, |_| true);
+        assert_eq!(render(&field.dist), "01221");
+    }
+}
@@ -0,0 +1,565 @@
+use crate::math::{gcd, gcd_u128};
+use crate::rect::Rect;
+use crate::vec2::{Dir, Vec2i};
+
+/// Turn direction of a path a -> b -> c as seen on screen (x east, y south).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Turn {
+    Left,
+    Straight,
+    Right,
+}
+
+/// Cross product in i128, which cannot overflow for i64 inputs.
+fn wide_cross(a: &Vec2i, b: &Vec2i) -> i128 {
+    a.x as i128 * b.y as i128 - a.y as i128 * b.x as i128
+}
+
+/// Dot product in i128, which cannot overflow for i64 inputs.
+fn wide_dot(a: &Vec2i, b: &Vec2i) -> i128 {
+    a.x as i128 * b.x as i128 + a.y as i128 * b.y as i128
+}
+
+/// Turn direction of a -> b -> c.
+pub fn orientation(a: &Vec2i, b: &Vec2i, c: &Vec2i) -> Turn {
+    // differences are taken in i128 as well, so any i64 coordinates work
+    let (ux, uy) = (b.x as i128 - a.x as i128, b.y as i128 - a.y as i128);
+    let (vx, vy) = (c.x as i128 - a.x as i128, c.y as i128 - a.y as i128);
+    match (ux * vy - uy * vx).signum() {
+        1 => Turn::Right,
+        -1 => Turn::Left,
+        _ => Turn::Straight,
+    }
+}
+
+/// Check if three points lie on a line.
+pub fn collinear(a: &Vec2i, b: &Vec2i, c: &Vec2i) -> bool {
+    orientation(a, b, c) == Turn::Straight
+}
+
+/// Number of lattice steps along d, i.e. the gcd of its components.
+fn lattice_len(d: &Vec2i) -> u64 {
+    match (d.x.unsigned_abs(), d.y.unsigned_abs()) {
+        (0, n) | (n, 0) => n,
+        (n, m) => gcd(n, m),
+    }
+}
+
+/// Smallest lattice step along d, i.e. d divided by the gcd of its components.
+///
+/// Returns zero for a zero vector.
+pub fn primitive_step(d: &Vec2i) -> Vec2i {
+    match lattice_len(d) {
+        0 => *d,
+        g => *d / g as i64,
+    }
+}
+
+/// Lattice points on the segment from a to b, inclusive, in order from a.
+pub fn segment_points(a: &Vec2i, b: &Vec2i) -> impl Iterator<Item = Vec2i> {
+    let step = primitive_step(&(*b - *a));
+    let n = match step {
+        Vec2i { x: 0, y: 0 } => 0,
+        _ => (*b - *a).chebyshev_norm() / step.chebyshev_norm(),
+    };
+    let a = *a;
+    (0..=n).map(move |k| a + step * k)
+}
+
+/// Lattice points of the infinite line through a and b that lie in rect, in order along b - a.
+///
+/// If a == b the line degenerates to the single point a.
+pub fn line_points_in(a: &Vec2i, b: &Vec2i, rect: &Rect) -> impl Iterator<Item = Vec2i> {
+    let step = primitive_step(&(*b - *a));
+    // range of k such that a + k * step is inside rect, per axis
+    let axis_range = |p: i64, s: i64, lo: i64, hi: i64| -> (i64, i64) {
+        match s.signum() {
+            0 if (lo..hi).contains(&p) => (i64::MIN, i64::MAX),
+            0 => (1, 0),
+            1 => (div_ceil(lo - p, s), (hi - 1 - p).div_euclid(s)),
+            _ => (div_ceil(p - (hi - 1), -s), (p - lo).div_euclid(-s)),
+        }
+    };
+    let (x0, x1) = axis_range(a.x, step.x, rect.min.x, rect.max.x);
+    let (y0, y1) = axis_range(a.y, step.y, rect.min.y, rect.max.y);
+    let (k0, k1) = match step {
+        Vec2i { x: 0, y: 0 } if rect.contains(a) => (0, 0),
+        Vec2i { x: 0, y: 0 } => (1, 0),
+        _ => (x0.max(y0), x1.min(y1)),
+    };
+    let a = *a;
+    (k0..=k1).map(move |k| a + step * k)
+}
+
+fn div_ceil(n: i64, d: i64) -> i64 {
+    -(-n).div_euclid(d)
+}
+
+/// Rasterize the segment from a to b with Bresenham's algorithm.
+///
+/// Consecutive points are 8-connected. Includes both endpoints.
+pub fn bresenham(a: &Vec2i, b: &Vec2i) -> Vec<Vec2i> {
+    let d = (*b - *a).abs();
+    let s = (*b - *a).signum();
+    let mut err = d.x - d.y;
+    let mut cur = *a;
+    let mut ret = vec![cur];
+    while cur != *b {
+        let e2 = 2 * err;
+        if e2 > -d.y {
+            err -= d.y;
+            cur.x += s.x;
+        }
+        if e2 < d.x {
+            err += d.x;
+            cur.y += s.y;
+        }
+        ret.push(cur);
+    }
+    ret
+}
+
+/// Point with rational coordinates (x / den, y / den), den > 0 and fully reduced.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RationalPoint {
+    pub x: i128,
+    pub y: i128,
+    pub den: i128,
+}
+
+impl RationalPoint {
+    pub fn new(x: i128, y: i128, den: i128) -> Self {
+        assert_ne!(den, 0);
+        let s = den.signum();
+        let g = gcd_u128(x.unsigned_abs(), y.unsigned_abs());
+        let g = gcd_u128(g, den.unsigned_abs()) as i128;
+        RationalPoint {
+            x: s * x / g,
+            y: s * y / g,
+            den: s * den / g,
+        }
+    }
+
+    /// The point as a lattice point, None if it is not one.
+    pub fn as_lattice(&self) -> Option<Vec2i> {
+        (self.den == 1).then(|| Vec2i::new(self.x as i64, self.y as i64))
+    }
+
+    pub fn to_f64(&self) -> (f64, f64) {
+        (
+            self.x as f64 / self.den as f64,
+            self.y as f64 / self.den as f64,
+        )
+    }
+}
+
+/// Result of intersecting two segments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SegmentIntersection {
+    /// Segments meet in a single point.
+    Point(RationalPoint),
+    /// Collinear segments share the segment between two lattice points.
+    Overlap(Vec2i, Vec2i),
+}
+
+/// Intersection of the closed segments p1-p2 and q1-q2.
+pub fn segment_intersection(
+    p1: &Vec2i,
+    p2: &Vec2i,
+    q1: &Vec2i,
+    q2: &Vec2i,
+) -> Option<SegmentIntersection> {
+    let cross = |a: Vec2i, b: Vec2i| wide_cross(&a, &b);
+    let (r, s, qp) = (*p2 - *p1, *q2 - *q1, *q1 - *p1);
+    let den = cross(r, s);
+
+    if den != 0 {
+        // p1 + t r = q1 + u s with t = tn / den, u = un / den
+        let (tn, un) = (cross(qp, s), cross(qp, r));
+        let inside = |n: i128| match den > 0 {
+            true => 0 <= n && n <= den,
+            false => den <= n && n <= 0,
+        };
+        if !inside(tn) || !inside(un) {
+            return None;
+        }
+        let x = p1.x as i128 * den + r.x as i128 * tn;
+        let y = p1.y as i128 * den + r.y as i128 * tn;
+        return Some(SegmentIntersection::Point(RationalPoint::new(x, y, den)));
+    }
+
+    // parallel, intersect only if collinear
+    let dir = if r != Vec2i::new(0, 0) { r } else { s };
+    if dir == Vec2i::new(0, 0) {
+        // both segments are points
+        return (p1 == q1).then(|| {
+            SegmentIntersection::Point(RationalPoint::new(p1.x as i128, p1.y as i128, 1))
+        });
+    }
+    if cross(qp, dir) != 0 || cross(*q2 - *p1, dir) != 0 {
+        return None;
+    }
+    let key = |p: &Vec2i| wide_dot(&(*p - *p1), &dir);
+    let (pa, pb) = if key(p1) <= key(p2) {
+        (p1, p2)
+    } else {
+        (p2, p1)
+    };
+    let (qa, qb) = if key(q1) <= key(q2) {
+        (q1, q2)
+    } else {
+        (q2, q1)
+    };
+    let lo = if key(pa) >= key(qa) { pa } else { qa };
+    let hi = if key(pb) <= key(qb) { pb } else { qb };
+    match key(lo).cmp(&key(hi)) {
+        std::cmp::Ordering::Greater => None,
+        std::cmp::Ordering::Equal => Some(SegmentIntersection::Point(RationalPoint::new(
+            lo.x as i128,
+            lo.y as i128,
+            1,
+        ))),
+        std::cmp::Ordering::Less => Some(SegmentIntersection::Overlap(*lo, *hi)),
+    }
+}
+
+/// Simple polygon on the lattice, with an implicit edge from the last vertex to the first.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Polygon {
+    pub vertices: Vec<Vec2i>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vec2i>) -> Self {
+        Polygon { vertices }
+    }
+
+    /// Trace a polygon by moving from start according to (direction, length) instructions.
+    ///
+    /// The final position is expected to be back at start and is not repeated.
+    pub fn from_instructions<I>(start: &Vec2i, instructions: I) -> Self
+    where
+        I: IntoIterator<Item = (Dir, i64)>,
+    {
+        let mut vertices = vec![*start];
+        for (dir, n) in instructions {
+            vertices.push(vertices.last().unwrap().step(dir, n));
+        }
+        if vertices.len() > 1 && vertices.last() == vertices.first() {
+            vertices.pop();
+        }
+        Polygon { vertices }
+    }
+
+    /// Iterate over edges (from, to).
+    pub fn edges(&self) -> impl Iterator<Item = (&Vec2i, &Vec2i)> {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    /// Twice the signed area from the shoelace formula.
+    ///
+    /// Positive for polygons that run clockwise on screen (x east, y south).
+    pub fn signed_double_area(&self) -> i128 {
+        self.edges().map(|(a, b)| wide_cross(a, b)).sum()
+    }
+
+    /// Twice the enclosed area.
+    pub fn double_area(&self) -> u128 {
+        self.signed_double_area().unsigned_abs()
+    }
+
+    /// Winding direction: Right for clockwise and Left for counter-clockwise on screen.
+    pub fn winding(&self) -> Turn {
+        match self.signed_double_area().signum() {
+            1 => Turn::Right,
+            -1 => Turn::Left,
+            _ => Turn::Straight,
+        }
+    }
+
+    /// Euclidean length of the boundary.
+    pub fn perimeter(&self) -> f64 {
+        // hypot in f64 instead of the i64 dot product, which overflows for long edges
+        self.edges()
+            .map(|(a, b)| (b.x as f64 - a.x as f64).hypot(b.y as f64 - a.y as f64))
+            .sum()
+    }
+
+    /// Number of lattice points on the boundary.
+    pub fn boundary_points(&self) -> u64 {
+        self.edges().map(|(a, b)| lattice_len(&(*b - *a))).sum()
+    }
+
+    /// Number of lattice points strictly inside, from Pick's theorem.
+    ///
+    /// Degenerate polygons without area have no interior points.
+    pub fn interior_points(&self) -> u128 {
+        // A = I + B / 2 - 1
+        (self.double_area() + 2)
+            .checked_sub(self.boundary_points() as u128)
+            .map_or(0, |n| n / 2)
+    }
+
+    /// Number of lattice points inside or on the boundary.
+    pub fn lattice_points(&self) -> u128 {
+        self.interior_points() + self.boundary_points() as u128
+    }
+
+    /// Check if p lies on an edge.
+    pub fn on_boundary(&self, p: &Vec2i) -> bool {
+        self.edges().any(|(a, b)| {
+            collinear(a, b, p) && Rect::from_points([a, b]).is_some_and(|r| r.contains(p))
+        })
+    }
+
+    /// Check if p is inside the polygon or on its boundary.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        if self.on_boundary(p) {
+            return true;
+        }
+        // count crossings of a ray towards -x, using half-open edges in y
+        let crossings = self
+            .edges()
+            .filter(|(a, b)| (a.y > p.y) != (b.y > p.y))
+            .filter(|(a, b)| {
+                let (lo, hi) = if a.y < b.y { (a, b) } else { (b, a) };
+                orientation(lo, hi, p) == Turn::Left
+            })
+            .count();
+        crossings % 2 == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orientation() {
+        let (a, b) = (Vec2i::new(0, 0), Vec2i::new(2, 0));
+        assert_eq!(orientation(&a, &b, &Vec2i::new(3, 1)), Turn::Right);
+        assert_eq!(orientation(&a, &b, &Vec2i::new(3, -1)), Turn::Left);
+        assert_eq!(orientation(&a, &b, &Vec2i::new(-5, 0)), Turn::Straight);
+        assert!(collinear(&a, &Vec2i::new(1, 1), &Vec2i::new(-3, -3)));
+    }
+
+    #[test]
+    fn test_segment_points() {
+        let pts: Vec<_> = segment_points(&Vec2i::new(0, 0), &Vec2i::new(6, -4)).collect();
+        assert_eq!(
+            pts,
+            vec![Vec2i::new(0, 0), Vec2i::new(3, -2), Vec2i::new(6, -4)]
+        );
+        assert_eq!(
+            segment_points(&Vec2i::new(1, 1), &Vec2i::new(1, 1)).count(),
+            1
+        );
+        assert_eq!(primitive_step(&Vec2i::new(0, -7)), Vec2i::new(0, -1));
+    }
+
+    #[test]
+    fn test_line_points_in() {
+        let rect = Rect::from_size(&Vec2i::new(0, 0), 10, 12);
+        for (a, b) in [
+            (Vec2i::new(3, 4), Vec2i::new(5, 5)),
+            (Vec2i::new(-7, 20), Vec2i::new(-5, 17)),
+            (Vec2i::new(2, 2), Vec2i::new(2, 9)),
+            (Vec2i::new(0, 30), Vec2i::new(1, 30)),
+            (Vec2i::new(4, 4), Vec2i::new(4, 4)),
+        ] {
+            let step = primitive_step(&(b - a));
+            let expected: Vec<_> = (-100..=100)
+                .map(|k| a + step * k)
+                .filter(|p| rect.contains(p))
+                .collect::<Vec<_>>();
+            let expected = if step == Vec2i::new(0, 0) {
+                vec![a]
+            } else {
+                expected
+            };
+            assert_eq!(line_points_in(&a, &b, &rect).collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_bresenham() {
+        let line = bresenham(&Vec2i::new(0, 0), &Vec2i::new(5, 2));
+        assert_eq!(line.len(), 6);
+        assert_eq!(line[0], Vec2i::new(0, 0));
+        assert_eq!(*line.last().unwrap(), Vec2i::new(5, 2));
+        for w in line.windows(2) {
+            assert_eq!((w[1] - w[0]).chebyshev_norm(), 1);
+        }
+        let line = bresenham(&Vec2i::new(3, 3), &Vec2i::new(-1, 3));
+        assert_eq!(
+            line,
+            (-1..=3).rev().map(|x| Vec2i::new(x, 3)).collect::<Vec<_>>()
+        );
+        assert_eq!(bresenham(&Vec2i::new(1, 1), &Vec2i::new(1, 1)).len(), 1);
+    }
+
+    #[test]
+    fn test_segment_intersection() {
+        let v = Vec2i::new;
+        let point = |x, y, den| Some(SegmentIntersection::Point(RationalPoint::new(x, y, den)));
+
+        // crossing at a lattice point and at a fractional point
+        let hit = segment_intersection(&v(0, 0), &v(4, 4), &v(0, 4), &v(4, 0));
+        assert_eq!(hit, point(2, 2, 1));
+        let hit = segment_intersection(&v(0, 0), &v(1, 1), &v(0, 1), &v(1, 0));
+        assert_eq!(hit, point(1, 1, 2));
+        match hit {
+            Some(SegmentIntersection::Point(p)) => {
+                assert_eq!(p.as_lattice(), None);
+                assert_eq!(p.to_f64(), (0.5, 0.5));
+            }
+            _ => unreachable!(),
+        }
+
+        // touching at an endpoint, missing, parallel
+        assert_eq!(
+            segment_intersection(&v(0, 0), &v(2, 0), &v(2, 0), &v(2, 5)),
+            point(2, 0, 1)
+        );
+        assert_eq!(
+            segment_intersection(&v(0, 0), &v(2, 0), &v(3, -1), &v(3, 1)),
+            None
+        );
+        assert_eq!(
+            segment_intersection(&v(0, 0), &v(2, 0), &v(0, 1), &v(2, 1)),
+            None
+        );
+
+        // collinear
+        assert_eq!(
+            segment_intersection(&v(0, 0), &v(4, 2), &v(6, 3), &v(2, 1)),
+            Some(SegmentIntersection::Overlap(v(2, 1), v(4, 2)))
+        );
+        assert_eq!(
+            segment_intersection(&v(0, 0), &v(2, 2), &v(2, 2), &v(5, 5)),
+            point(2, 2, 1)
+        );
+        assert_eq!(
+            segment_intersection(&v(0, 0), &v(1, 1), &v(2, 2), &v(5, 5)),
+            None
+        );
+        assert_eq!(
+            segment_intersection(&v(1, 1), &v(1, 1), &v(0, 0), &v(3, 3)),
+            point(1, 1, 1)
+        );
+        assert_eq!(
+            segment_intersection(&v(1, 1), &v(1, 1), &v(0, 0), &v(0, 0)),
+            None
+        );
+
+        // collinear overlap far beyond where an i64 dot product overflows
+        let big = 2_000_000_000;
+        assert_eq!(
+            segment_intersection(
+                &v(0, 0),
+                &v(2 * big, big),
+                &v(3 * big, 3 * big / 2),
+                &v(big, big / 2)
+            ),
+            Some(SegmentIntersection::Overlap(
+                v(big, big / 2),
+                v(2 * big, big)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_polygon_area() {
+        // 3 x 2 rectangle traced clockwise on screen
+        let rect = Polygon::from_instructions(
+            &Vec2i::new(0, 0),
+            [(Dir::E, 3), (Dir::S, 2), (Dir::W, 3), (Dir::N, 2)],
+        );
+        assert_eq!(rect.vertices.len(), 4);
+        assert_eq!(rect.signed_double_area(), 12);
+        assert_eq!(rect.winding(), Turn::Right);
+        assert_eq!(rect.boundary_points(), 10);
+        assert_eq!(rect.interior_points(), 2);
+        assert_eq!(rect.lattice_points(), 12);
+        assert_eq!(rect.perimeter(), 10.0);
+
+        let reversed = Polygon::new(rect.vertices.iter().rev().cloned().collect());
+        assert_eq!(reversed.signed_double_area(), -12);
+        assert_eq!(reversed.winding(), Turn::Left);
+        assert_eq!(reversed.interior_points(), 2);
+
+        // triangle with half-integer area
+        let tri = Polygon::new(vec![Vec2i::new(0, 0), Vec2i::new(3, 0), Vec2i::new(0, 3)]);
+        assert_eq!(tri.double_area(), 9);
+        assert_eq!(tri.boundary_points(), 9);
+        assert_eq!(tri.interior_points(), 1);
+    }
+
+    #[test]
+    fn test_polygon_large() {
+        let n = 3_000_000_000;
+        let square = Polygon::from_instructions(
+            &Vec2i::new(-n, -n),
+            [
+                (Dir::E, 2 * n),
+                (Dir::S, 2 * n),
+                (Dir::W, 2 * n),
+                (Dir::N, 2 * n),
+            ],
+        );
+        assert_eq!(square.double_area(), 2 * (2 * n as u128).pow(2));
+        assert_eq!(square.lattice_points(), (2 * n as u128 + 1).pow(2));
+        assert!(square.contains(&Vec2i::new(0, 0)));
+        assert!(square.contains(&Vec2i::new(n - 1, 1 - n)));
+        assert!(!square.contains(&Vec2i::new(n + 1, 0)));
+        assert!(square.on_boundary(&Vec2i::new(n, 12345)));
+        assert!(square.on_boundary(&Vec2i::new(-n, -n)));
+        assert!(!square.on_boundary(&Vec2i::new(n - 1, 12345)));
+        assert_eq!(square.perimeter(), 8.0 * n as f64);
+
+        let m = 1_000_000_000;
+        let triangle = Polygon::new(vec![
+            Vec2i::new(0, 0),
+            Vec2i::new(4 * m, 0),
+            Vec2i::new(0, 3 * m),
+        ]);
+        assert_eq!(triangle.perimeter(), 12.0 * m as f64);
+    }
+
+    #[test]
+    fn test_polygon_degenerate() {
+        let segment = Polygon::new(vec![Vec2i::new(0, 0), Vec2i::new(4, 2)]);
+        assert_eq!(segment.double_area(), 0);
+        assert_eq!(segment.interior_points(), 0);
+        let line = Polygon::new(vec![Vec2i::new(0, 0), Vec2i::new(3, 0), Vec2i::new(7, 0)]);
+        assert_eq!(line.interior_points(), 0);
+        assert!(line.on_boundary(&Vec2i::new(5, 0)));
+    }
+
+    #[test]
+    fn test_polygon_contains() {
+        // L-shaped polygon
+        let poly = Polygon::from_instructions(
+            &Vec2i::new(0, 0),
+            [
+                (Dir::E, 2),
+                (Dir::S, 2),
+                (Dir::E, 2),
+                (Dir::S, 2),
+                (Dir::W, 4),
+                (Dir::N, 4),
+            ],
+        );
+        let inside = Rect::from_size(&Vec2i::new(-1, -1), 7, 7)
+            .iter_points()
+            .filter(|p| poly.contains(p))
+            .count();
+        assert_eq!(inside as u128, poly.lattice_points());
+        assert!(poly.contains(&Vec2i::new(1, 3)));
+        assert!(poly.on_boundary(&Vec2i::new(3, 2)));
+        assert!(!poly.contains(&Vec2i::new(3, 1)));
+        assert!(!poly.contains(&Vec2i::new(5, 3)));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::vec2::Vec2i;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub};
+
+/// D-dimensional integer vector.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct VecN<const D: usize>(pub [i64; D]);
+
+impl<const D: usize> VecN<D> {
+    pub const ZERO: Self = VecN([0; D]);
+
+    pub fn new(coords: [i64; D]) -> Self {
+        VecN(coords)
+    }
+
+    /// Unit vector along axis.
+    pub fn unit(axis: usize) -> Self {
+        let mut ret = Self::ZERO;
+        ret.0[axis] = 1;
+        ret
+    }
+
+    /// Check if point is inside a grid of the given shape.
+    pub fn is_in_grid(&self, shape: &[usize; D]) -> bool {
+        self.0
+            .iter()
+            .zip(shape)
+            .all(|(x, n)| 0 <= *x && *x < *n as i64)
+    }
+
+    /// Linear index in a grid where the first axis varies fastest.
+    pub fn linear_idx(&self, shape: &[usize; D]) -> usize {
+        self.0
+            .iter()
+            .zip(shape)
+            .rev()
+            .fold(0, |acc, (x, n)| acc * n + *x as usize)
+    }
+
+    pub fn manhattan_norm(&self) -> u64 {
+        self.0.iter().map(|x| x.unsigned_abs()).sum()
+    }
+
+    pub fn manhattan_dist(&self, other: &Self) -> u64 {
+        (*self - *other).manhattan_norm()
+    }
+}
+
+impl<const D: usize> From<[i64; D]> for VecN<D> {
+    fn from(coords: [i64; D]) -> Self {
+        VecN(coords)
+    }
+}
+
+impl From<Vec2i> for VecN<2> {
+    fn from(p: Vec2i) -> Self {
+        VecN([p.x, p.y])
+    }
+}
+
+impl From<VecN<2>> for Vec2i {
+    fn from(p: VecN<2>) -> Self {
+        Vec2i::new(p.0[0], p.0[1])
+    }
+}
+
+impl<const D: usize> Index<usize> for VecN<D> {
+    type Output = i64;
+    fn index(&self, axis: usize) -> &i64 {
+        &self.0[axis]
+    }
+}
+
+impl<const D: usize> IndexMut<usize> for VecN<D> {
+    fn index_mut(&mut self, axis: usize) -> &mut i64 {
+        &mut self.0[axis]
+    }
+}
+
+impl<const D: usize> Add for VecN<D> {
+    type Output = Self;
+    fn add(mut self, o: Self) -> Self {
+        self += o;
+        self
+    }
+}
+
+impl<const D: usize> AddAssign for VecN<D> {
+    fn add_assign(&mut self, o: Self) {
+        for (a, b) in self.0.iter_mut().zip(o.0) {
+            *a += b;
+        }
+    }
+}
+
+impl<const D: usize> Sub for VecN<D> {
+    type Output = Self;
+    fn sub(mut self, o: Self) -> Self {
+        for (a, b) in self.0.iter_mut().zip(o.0) {
+            *a -= b;
+        }
+        self
+    }
+}
+
+impl<const D: usize> Mul<i64> for VecN<D> {
+    type Output = Self;
+    fn mul(mut self, k: i64) -> Self {
+        for a in self.0.iter_mut() {
+            *a *= k;
+        }
+        self
+    }
+}
+
+/// Offsets to the 2 * D orthogonal neighbors.
+pub fn orthogonal_offsets<const D: usize>() -> impl Iterator<Item = VecN<D>> {
+    (0..D).flat_map(|axis| [VecN::unit(axis) * -1, VecN::unit(axis)])
+}
+
+/// Offsets to the 3^D - 1 neighbors that differ by at most one along every axis.
+pub fn moore_offsets<const D: usize>() -> impl Iterator<Item = VecN<D>> {
+    (0..3usize.pow(D as u32))
+        .map(|mut k| {
+            let mut ret = VecN::ZERO;
+            for x in ret.0.iter_mut() {
+                *x = (k % 3) as i64 - 1;
+                k /= 3;
+            }
+            ret
+        })
+        .filter(|d| *d != VecN::ZERO)
+}
+
+/// D-dimensional grid.
+///
+/// Grid<T, D> is indexed by VecN<D> and stored with the first axis varying
+/// fastest, so a Grid<T, 2> has the same layout as a Map<T> with shape [w, h].
+#[derive(Debug, Clone)]
+pub struct Grid<T, const D: usize> {
+    pub shape: [usize; D],
+    data: Vec<T>,
+}
+
+impl<T, const D: usize> Grid<T, D> {
+    /// Create grid filled with default value.
+    pub fn new(shape: [usize; D]) -> Self
+    where
+        T: Clone + Default,
+    {
+        Self::new_constant(shape, T::default())
+    }
+
+    /// Create grid filled with constant.
+    pub fn new_constant(shape: [usize; D], t: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            shape,
+            data: vec![t; shape.iter().product()],
+        }
+    }
+
+    /// Create grid from flat data.
+    pub fn from_vec(shape: [usize; D], data: Vec<T>) -> Self {
+        assert_eq!(data.len(), shape.iter().product::<usize>());
+        Grid { shape, data }
+    }
+
+    /// Take out the flat data.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+
+    /// Create new grid with same shape.
+    pub fn same_size_with<S: Clone>(&self, val: S) -> Grid<S, D> {
+        Grid::new_constant(self.shape, val)
+    }
+
+    /// Create new grid by applying a function to each element.
+    pub fn map_values<S, F>(&self, f: F) -> Grid<S, D>
+    where
+        F: Fn(&T) -> S,
+    {
+        Grid {
+            shape: self.shape,
+            data: self.data.iter().map(f).collect(),
+        }
+    }
+
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if grid has no cells.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Check if coordinate is within grid bounds.
+    pub fn contains(&self, p: &VecN<D>) -> bool {
+        p.is_in_grid(&self.shape)
+    }
+
+    /// Get grid element.
+    pub fn get(&self, p: &VecN<D>) -> Option<&T> {
+        self.contains(p).then(|| &self[p])
+    }
+
+    /// Get mutable grid element.
+    pub fn get_mut(&mut self, p: &VecN<D>) -> Option<&mut T> {
+        match self.contains(p) {
+            true => Some(&mut self[p]),
+            false => None,
+        }
+    }
+
+    /// Iterate over orthogonal neighbors within grid.
+    pub fn iter_neighbors(&self, p: &VecN<D>) -> impl Iterator<Item = VecN<D>> + '_ {
+        let p = *p;
+        orthogonal_offsets()
+            .map(move |d| p + d)
+            .filter(|q| self.contains(q))
+    }
+
+    /// Iterate over all 3^D - 1 surrounding neighbors within grid.
+    pub fn iter_neighbors_moore(&self, p: &VecN<D>) -> impl Iterator<Item = VecN<D>> + '_ {
+        let p = *p;
+        moore_offsets()
+            .map(move |d| p + d)
+            .filter(|q| self.contains(q))
+    }
+
+    /// Iterate over coordinates in storage order.
+    pub fn iter_coords(&self) -> impl Iterator<Item = VecN<D>> {
+        let shape = self.shape;
+        (0..self.len()).map(move |mut i| {
+            let mut ret = VecN::ZERO;
+            for (x, n) in ret.0.iter_mut().zip(shape) {
+                *x = (i % n) as i64;
+                i /= n;
+            }
+            ret
+        })
+    }
+
+    /// Iterate over (coord, val) pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (VecN<D>, &T)> {
+        self.iter_coords().zip(self.data.iter())
+    }
+
+    /// Iterate over flattened grid.
+    pub fn iter_values(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+}
+
+/// Index with VecN.
+impl<T, const D: usize> Index<&VecN<D>> for Grid<T, D> {
+    type Output = T;
+    fn index(&self, p: &VecN<D>) -> &Self::Output {
+        debug_assert!(self.contains(p));
+        &self.data[p.linear_idx(&self.shape)]
+    }
+}
+
+/// Mutable index with VecN.
+impl<T, const D: usize> IndexMut<&VecN<D>> for Grid<T, D> {
+    fn index_mut(&mut self, p: &VecN<D>) -> &mut Self::Output {
+        debug_assert!(self.contains(p));
+        &mut self.data[p.linear_idx(&self.shape)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map2d::Map;
+
+    #[test]
+    fn test_offsets() {
+        assert_eq!(orthogonal_offsets::<3>().count(), 6);
+        assert_eq!(moore_offsets::<2>().count(), 8);
+        assert_eq!(moore_offsets::<4>().count(), 80);
+        assert!(moore_offsets::<3>().all(|d| d.0.iter().all(|x| x.abs() <= 1)));
+    }
+
+    #[test]
+    fn test_grid_3d() {
+        let mut grid = Grid::<u32, 3>::new([4, 3, 2]);
+        assert_eq!(grid.len(), 24);
+
+        let p = VecN([3, 2, 1]);
+        grid[&p] = 7;
+        assert_eq!(grid.get(&p), Some(&7));
+        assert_eq!(grid.get(&VecN([4, 0, 0])), None);
+        assert_eq!(grid.iter_values().last(), Some(&7));
+
+        assert_eq!(grid.iter_neighbors(&p).count(), 3);
+        assert_eq!(grid.iter_neighbors(&VecN([1, 1, 0])).count(), 5);
+        assert_eq!(grid.iter_neighbors_moore(&p).count(), 7);
+        assert_eq!(grid.iter_neighbors_moore(&VecN([1, 1, 1])).count(), 17);
+
+        for (i, (c, _)) in grid.iter().enumerate() {
+            assert_eq!(c.linear_idx(&grid.shape), i);
+        }
+    }
+
+    #[test]
+    fn test_life_4d() {
+        // one step of a 4D cellular automaton: a cell is alive next step if it has
+        // exactly 3 live neighbors, or is alive and has 2 live neighbors
+        let mut grid = Grid::<bool, 4>::new([5, 5, 3, 3]);
+        for x in 1..4 {
+            grid[&VecN([x, 2, 1, 1])] = true;
+        }
+        let next = grid.map_values(|_| false);
+        let next = next.iter_coords().fold(next, |mut next, p| {
+            let n = grid.iter_neighbors_moore(&p).filter(|q| grid[q]).count();
+            next[&p] = n == 3 || (grid[&p] && n == 2);
+            next
+        });
+        // the center of the bar survives, and every cell adjacent to all three is born
+        assert!(next[&VecN([2, 2, 1, 1])]);
+        assert!(!next[&VecN([1, 2, 1, 1])]);
+        assert_eq!(next.iter_values().filter(|b| **b).count(), 1 + 26);
+    }
+
+    #[test]
+    fn test_map_conversion() {
+        let map: Map<char> = "abc\ndef".parse().unwrap();
+        let grid: Grid<char, 2> = map.clone().into();
+        assert_eq!(grid.shape, [3, 2]);
+        for (p, c) in map.iter() {
+            assert_eq!(grid[&p.into()], *c);
+        }
+        let back: Map<char> = grid.into();
+        assert_eq!(back.to_string(), map.to_string());
+    }
+}
@@ -34,10 +34,52 @@ where
         }
     }
 
+    /// Build heap from unordered data in O(n).
+    pub fn from_vec(data: Vec<T>, cmp: F) -> Self {
+        let mut heap = MinHeap { data, cmp };
+        heap.heapify();
+        heap
+    }
+
+    /// Take out all elements in increasing order.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        // heapsort: moving the minimum to the back leaves the data in decreasing order
+        for end in (1..self.data.len()).rev() {
+            self.data.swap(0, end);
+            self.sift_down(0, end);
+        }
+        self.data.reverse();
+        self.data
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
     pub fn clear(&mut self) {
         self.data.clear();
     }
 
+    /// Iterate over elements in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+
+    /// Remove all elements, in arbitrary order.
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        self.data.drain(..)
+    }
+
+    /// Keep only the elements for which f returns true.
+    pub fn retain(&mut self, f: impl FnMut(&T) -> bool) {
+        self.data.retain(f);
+        self.heapify();
+    }
+
     pub fn push(&mut self, t: T) {
         self.data.push(t);
         self.bubble_up();
@@ -58,19 +100,33 @@ where
         ret
     }
 
-    pub fn replace(&mut self, t: T) -> Option<T>
-    where
-        T: Copy,
-    {
+    /// Pop the smallest element and push t, in a single sift.
+    ///
+    /// Does nothing on an empty heap and returns None; use `push_pop` to
+    /// also handle the empty case.
+    pub fn replace(&mut self, t: T) -> Option<T> {
         if self.data.is_empty() {
             return None;
         }
-        let ret = self.data[0];
-        self.data[0] = t;
+        let ret = std::mem::replace(&mut self.data[0], t);
         self.push_down();
         Some(ret)
     }
 
+    /// Push t and pop the smallest element, which may be t itself.
+    pub fn push_pop(&mut self, t: T) -> T {
+        match self.data.first() {
+            Some(top) if (self.cmp)(top, &t) == Ordering::Less => self.replace(t).unwrap(),
+            _ => t,
+        }
+    }
+
+    fn heapify(&mut self) {
+        for n in (0..self.data.len() / 2).rev() {
+            self.sift_down(n, self.data.len());
+        }
+    }
+
     fn bubble_up(&mut self) {
         // bubble up last element to preserve heap structure
         // indexing: parent of n is (n-1)/2
@@ -84,15 +140,19 @@ where
 
     fn push_down(&mut self) {
         // push down first element to preserve heap structure
+        self.sift_down(0, self.data.len());
+    }
+
+    fn sift_down(&mut self, mut n: usize, len: usize) {
+        // push down element n, only looking at the first len elements
         // indexing: children of n are 2n+1, 2n+2
-        let mut n = 0;
-        while 2 * n + 1 < self.data.len() {
+        while 2 * n + 1 < len {
             // while not child
             let n1 = 2 * n + 1;
             let n2 = 2 * n + 2;
 
             // find child with smallest value
-            let n_child = if n1 == self.data.len() - 1 {
+            let n_child = if n1 == len - 1 {
                 n1 // just one child node
             } else {
                 match (self.cmp)(&self.data[n1], &self.data[n2]) {
@@ -112,9 +172,208 @@ where
     }
 }
 
+impl<T, F> Extend<T> for MinHeap<T, F>
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.data.reserve(iter.size_hint().0);
+        iter.for_each(|t| self.push(t));
+    }
+}
+
+/// The k smallest elements of iter in increasing order.
+///
+/// Keeps a heap of the best k elements seen so far, so this takes O(n log k).
+pub fn k_smallest<T, I, F>(iter: I, k: usize, cmp: F) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    if k == 0 {
+        return vec![];
+    }
+    // max-heap, so the worst of the current best k is on top
+    let mut heap = MinHeap::with_capacity(k, |a: &T, b: &T| cmp(b, a));
+    for t in iter {
+        if heap.len() < k {
+            heap.push(t);
+        } else {
+            heap.push_pop(t);
+        }
+    }
+    let mut ret = heap.into_sorted_vec();
+    ret.reverse();
+    ret
+}
+
+/// Merge sorted iterators into a single sorted iterator.
+///
+/// Equal elements are yielded in the order of the iterators they come from.
+pub fn merge_sorted<T, I, F>(mut iters: Vec<I>, cmp: F) -> impl Iterator<Item = T>
+where
+    I: Iterator<Item = T>,
+    F: Fn(&T, &T) -> Ordering,
+{
+    let heads = iters
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, it)| Some((it.next()?, i)))
+        .collect();
+    let mut heap = MinHeap::from_vec(heads, move |a: &(T, usize), b: &(T, usize)| {
+        cmp(&a.0, &b.0).then(a.1.cmp(&b.1))
+    });
+    std::iter::from_fn(move || {
+        let (_, i) = heap.peek()?;
+        let i = *i;
+        let (t, _) = match iters[i].next() {
+            Some(next) => heap.replace((next, i)).unwrap(),
+            None => heap.pop().unwrap(),
+        };
+        Some(t)
+    })
+}
+
+/// Min-heap of keys with priorities that supports decrease-key.
+///
+/// Keys are dense indices (anything that converts into a usize), and each key is in the
+/// heap at most once. The position of every key is tracked, so priorities can be
+/// lowered or keys removed in O(log n) instead of pushing duplicates.
+#[derive(Debug, Clone)]
+pub struct IndexedMinHeap<K, P> {
+    data: Vec<(K, P)>,
+    pos: Vec<usize>, // key -> index in data, NONE if not in heap
+}
+
+impl<K, P> IndexedMinHeap<K, P>
+where
+    K: Copy + Into<usize>,
+    P: Copy + Ord,
+{
+    const NONE: usize = usize::MAX;
+
+    /// Create heap for keys in 0..num_keys; larger keys grow the index on demand.
+    pub fn new(num_keys: usize) -> Self {
+        IndexedMinHeap {
+            data: vec![],
+            pos: vec![Self::NONE; num_keys],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        for (k, _) in self.data.drain(..) {
+            self.pos[k.into()] = Self::NONE;
+        }
+    }
+
+    pub fn contains(&self, k: K) -> bool {
+        self.index_of(k).is_some()
+    }
+
+    /// Current priority of key, None if it is not in the heap.
+    pub fn priority_of(&self, k: K) -> Option<P> {
+        self.index_of(k).map(|i| self.data[i].1)
+    }
+
+    /// Insert key, or lower its priority if it is already in the heap.
+    ///
+    /// Returns true if the heap changed.
+    pub fn push_or_decrease(&mut self, k: K, p: P) -> bool {
+        match self.index_of(k) {
+            Some(i) if p < self.data[i].1 => {
+                self.data[i].1 = p;
+                self.bubble_up(i);
+                true
+            }
+            Some(_) => false,
+            None => {
+                let idx = k.into();
+                if idx >= self.pos.len() {
+                    self.pos.resize(idx + 1, Self::NONE);
+                }
+                self.data.push((k, p));
+                self.pos[idx] = self.data.len() - 1;
+                self.bubble_up(self.data.len() - 1);
+                true
+            }
+        }
+    }
+
+    pub fn peek(&self) -> Option<&(K, P)> {
+        self.data.first()
+    }
+
+    /// Remove key with smallest priority.
+    pub fn pop(&mut self) -> Option<(K, P)> {
+        let k = self.data.first()?.0;
+        self.remove(k).map(|p| (k, p))
+    }
+
+    /// Remove key from heap and return its priority.
+    pub fn remove(&mut self, k: K) -> Option<P> {
+        let i = self.index_of(k)?;
+        let last = self.data.len() - 1;
+        self.swap(i, last);
+        let (_, p) = self.data.pop().unwrap();
+        self.pos[k.into()] = Self::NONE;
+        if i < self.data.len() {
+            // the moved element can go either way
+            self.bubble_up(i);
+            self.push_down(i);
+        }
+        Some(p)
+    }
+
+    fn index_of(&self, k: K) -> Option<usize> {
+        self.pos.get(k.into()).copied().filter(|i| *i != Self::NONE)
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.data.swap(i, j);
+        self.pos[self.data[i].0.into()] = i;
+        self.pos[self.data[j].0.into()] = j;
+    }
+
+    fn bubble_up(&mut self, mut n: usize) {
+        while n >= 1 && self.data[(n - 1) / 2].1 > self.data[n].1 {
+            let n_parent = (n - 1) / 2;
+            self.swap(n, n_parent);
+            n = n_parent;
+        }
+    }
+
+    fn push_down(&mut self, mut n: usize) {
+        loop {
+            let (n1, n2) = (2 * n + 1, 2 * n + 2);
+            let mut n_child = n;
+            if n1 < self.data.len() && self.data[n1].1 < self.data[n_child].1 {
+                n_child = n1;
+            }
+            if n2 < self.data.len() && self.data[n2].1 < self.data[n_child].1 {
+                n_child = n2;
+            }
+            if n_child == n {
+                break;
+            }
+            self.swap(n, n_child);
+            n = n_child;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testutil::Lcg;
 
     #[test]
     fn test_bubble_up() {
@@ -247,4 +506,123 @@ mod tests {
         assert_eq!(heap.pop(), Some(5));
         assert_eq!(heap.pop(), None);
     }
+
+    #[test]
+    fn test_indexed_heap() {
+        let mut heap = IndexedMinHeap::new(4);
+        assert!(heap.push_or_decrease(3usize, 30));
+        assert!(heap.push_or_decrease(1, 10));
+        assert!(heap.push_or_decrease(7, 70)); // grows key index
+        assert!(heap.push_or_decrease(5, 50));
+        assert!(!heap.push_or_decrease(3, 35)); // not a decrease
+        assert!(heap.push_or_decrease(7, 5));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.priority_of(3), Some(30));
+        assert_eq!(heap.priority_of(2), None);
+        assert_eq!(heap.peek(), Some(&(7, 5)));
+
+        assert_eq!(heap.remove(1), Some(10));
+        assert_eq!(heap.remove(1), None);
+        assert!(!heap.contains(1));
+
+        assert_eq!(heap.pop(), Some((7, 5)));
+        assert_eq!(heap.pop(), Some((3, 30)));
+        assert_eq!(heap.pop(), Some((5, 50)));
+        assert_eq!(heap.pop(), None);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_indexed_heap_random() {
+        // compare against a plain array of priorities
+        let n = 50;
+        let mut heap = IndexedMinHeap::new(n);
+        let mut prio: Vec<Option<u64>> = vec![None; n];
+        let mut rng = Lcg::new(17);
+        for _ in 0..2000 {
+            let seed = rng.next_u64();
+            let k = (seed >> 33) as usize % n;
+            let p = (seed >> 40) % 1000;
+            match (seed >> 20) % 4 {
+                0 => {
+                    assert_eq!(heap.remove(k), prio[k].take());
+                }
+                1 => {
+                    let min = prio
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(k, p)| Some(((*p)?, k)))
+                        .min();
+                    let popped = heap.pop();
+                    assert_eq!(popped.map(|(_, p)| p), min.map(|(p, _)| p));
+                    if let Some((k, _)) = popped {
+                        prio[k] = None;
+                    }
+                }
+                _ => {
+                    let changed = prio[k].is_none_or(|q| p < q);
+                    assert_eq!(heap.push_or_decrease(k, p), changed);
+                    if changed {
+                        prio[k] = Some(p);
+                    }
+                }
+            }
+            assert_eq!(heap.len(), prio.iter().flatten().count());
+            assert!((0..n).all(|k| heap.priority_of(k) == prio[k]));
+        }
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let data = vec![5, 9, 1, 7, 3, 3, 8, 0, 2];
+        let heap = MinHeap::from_vec(data.clone(), |x: &i64, y: &i64| x.cmp(y));
+        assert_eq!(heap.len(), 9);
+        assert_eq!(heap.peek(), Some(&0));
+        let mut sorted = data.clone();
+        sorted.sort();
+        assert_eq!(heap.into_sorted_vec(), sorted);
+
+        let mut heap = MinHeap::from_vec(data, |x: &i64, y: &i64| x.cmp(y));
+        heap.retain(|x| x % 3 != 0);
+        assert_eq!(heap.iter().count(), 5);
+        heap.extend([4, -1]);
+        assert_eq!(heap.pop(), Some(-1));
+        let mut drained: Vec<_> = heap.drain().collect();
+        drained.sort();
+        assert_eq!(drained, vec![1, 2, 4, 5, 7, 8]);
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    fn test_push_pop() {
+        let mut heap = MinHeap::new(|x: &String, y: &String| x.len().cmp(&y.len()));
+        assert_eq!(heap.replace("abc".to_string()), None);
+        assert!(heap.is_empty());
+        assert_eq!(heap.push_pop("a".to_string()), "a");
+        heap.push("abc".to_string());
+        assert_eq!(heap.push_pop("abcd".to_string()), "abc");
+        assert_eq!(heap.replace("ab".to_string()), Some("abcd".to_string()));
+        assert_eq!(heap.pop(), Some("ab".to_string()));
+        assert_eq!(heap.pop(), None);
+    }
+
+    #[test]
+    fn test_k_smallest() {
+        let data = [5, 9, 1, 7, 3, 3, 8, 0, 2];
+        assert_eq!(k_smallest(data, 4, |x, y| x.cmp(y)), vec![0, 1, 2, 3]);
+        assert_eq!(k_smallest(data, 2, |x, y| y.cmp(x)), vec![9, 8]);
+        assert_eq!(k_smallest(data, 20, |x, y| x.cmp(y)).len(), 9);
+        assert!(k_smallest(data, 0, |x, y| x.cmp(y)).is_empty());
+    }
+
+    #[test]
+    fn test_merge_sorted() {
+        let streams = vec![vec![1, 4, 7], vec![], vec![2, 2, 9], vec![0, 4]];
+        let merged: Vec<_> = merge_sorted(
+            streams.into_iter().map(|v| v.into_iter()).collect(),
+            |x: &i32, y: &i32| x.cmp(y),
+        )
+        .collect();
+        assert_eq!(merged, vec![0, 1, 2, 2, 4, 4, 7, 9]);
+    }
 }
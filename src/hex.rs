@@ -0,0 +1,650 @@
+use crate::map2d::{GridTile, ParseMapError};
+use std::{
+    error::Error,
+    fmt,
+    ops::{Add, AddAssign, Index, IndexMut, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+/// Hex direction for pointy-top hexagons, listed counter-clockwise from E.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub enum HexDir {
+    E = 0,
+    NE = 1,
+    NW = 2,
+    W = 3,
+    SW = 4,
+    SE = 5,
+}
+
+pub const HEX_DIRECTIONS: [HexDir; 6] = [
+    HexDir::E,
+    HexDir::NE,
+    HexDir::NW,
+    HexDir::W,
+    HexDir::SW,
+    HexDir::SE,
+];
+
+impl HexDir {
+    /// Unit step in axial coordinates.
+    pub const fn to_hex(self) -> Hex {
+        match self {
+            HexDir::E => Hex::new(1, 0),
+            HexDir::NE => Hex::new(1, -1),
+            HexDir::NW => Hex::new(0, -1),
+            HexDir::W => Hex::new(-1, 0),
+            HexDir::SW => Hex::new(-1, 1),
+            HexDir::SE => Hex::new(0, 1),
+        }
+    }
+
+    /// Rotate 60 degrees clockwise.
+    pub fn turn_right(self) -> HexDir {
+        HEX_DIRECTIONS[(self as usize + 5) % 6]
+    }
+
+    /// Rotate 60 degrees counter-clockwise.
+    pub fn turn_left(self) -> HexDir {
+        HEX_DIRECTIONS[(self as usize + 1) % 6]
+    }
+
+    pub fn turn_around(self) -> HexDir {
+        HEX_DIRECTIONS[(self as usize + 3) % 6]
+    }
+
+    /// Parse a run-together path like "esenee".
+    pub fn parse_path(s: &str) -> Result<Vec<HexDir>, String> {
+        let mut ret = Vec::new();
+        let mut rest = s.trim();
+        while !rest.is_empty() {
+            let len = if rest.starts_with(['n', 's']) { 2 } else { 1 };
+            let token = rest.get(..len).ok_or_else(|| format!("Truncated {rest}"))?;
+            ret.push(token.parse()?);
+            rest = &rest[len..];
+        }
+        Ok(ret)
+    }
+}
+
+impl FromStr for HexDir {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "e" | "E" => Ok(HexDir::E),
+            "ne" | "NE" => Ok(HexDir::NE),
+            "nw" | "NW" => Ok(HexDir::NW),
+            "w" | "W" => Ok(HexDir::W),
+            "sw" | "SW" => Ok(HexDir::SW),
+            "se" | "SE" => Ok(HexDir::SE),
+            _ => Err(format!("Invalid hex direction {s}")),
+        }
+    }
+}
+
+impl Neg for HexDir {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        self.turn_around()
+    }
+}
+
+/// Axial hex coordinate.
+///
+/// q grows to the E and r to the SE, so rows of constant r are horizontal.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+/// Cube hex coordinate with q + r + s = 0.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Cube {
+    pub q: i64,
+    pub r: i64,
+    pub s: i64,
+}
+
+impl From<Hex> for Cube {
+    fn from(h: Hex) -> Self {
+        Cube {
+            q: h.q,
+            r: h.r,
+            s: h.s(),
+        }
+    }
+}
+
+impl From<Cube> for Hex {
+    fn from(c: Cube) -> Self {
+        debug_assert_eq!(c.q + c.r + c.s, 0);
+        Hex::new(c.q, c.r)
+    }
+}
+
+impl Cube {
+    /// Round fractional cube coordinates to the nearest hex.
+    pub fn round(q: f64, r: f64, s: f64) -> Cube {
+        let (mut rq, mut rr, mut rs) = (q.round(), r.round(), s.round());
+        let (dq, dr, ds) = ((rq - q).abs(), (rr - r).abs(), (rs - s).abs());
+        if dq > dr && dq > ds {
+            rq = -rr - rs;
+        } else if dr > ds {
+            rr = -rq - rs;
+        } else {
+            rs = -rq - rr;
+        }
+        Cube {
+            q: rq as i64,
+            r: rr as i64,
+            s: rs as i64,
+        }
+    }
+}
+
+impl Hex {
+    pub const ORIGIN: Hex = Hex::new(0, 0);
+
+    #[inline]
+    pub const fn new(q: i64, r: i64) -> Self {
+        Hex { q, r }
+    }
+
+    /// Third cube coordinate.
+    pub const fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    /// Move n steps in direction.
+    pub fn step(&self, dir: HexDir, n: i64) -> Hex {
+        *self + dir.to_hex() * n
+    }
+
+    /// The six adjacent hexes, in HEX_DIRECTIONS order.
+    pub fn neighbors(&self) -> impl Iterator<Item = Hex> {
+        let p = *self;
+        HEX_DIRECTIONS.into_iter().map(move |d| p.step(d, 1))
+    }
+
+    /// Number of steps from origin.
+    pub fn norm(&self) -> u64 {
+        (self.q.unsigned_abs() + self.r.unsigned_abs() + self.s().unsigned_abs()) / 2
+    }
+
+    /// Number of steps between hexes.
+    pub fn dist(&self, other: &Hex) -> u64 {
+        (*self - *other).norm()
+    }
+
+    /// Rotate 60 degrees clockwise around origin.
+    pub fn rotate_cw(&self) -> Hex {
+        Hex::new(-self.r, -self.s())
+    }
+
+    /// Rotate 60 degrees counter-clockwise around origin.
+    pub fn rotate_ccw(&self) -> Hex {
+        Hex::new(-self.s(), -self.q)
+    }
+
+    /// Rotate k * 60 degrees clockwise around center.
+    pub fn rotate_around(&self, center: &Hex, k: i64) -> Hex {
+        let mut d = *self - *center;
+        for _ in 0..k.rem_euclid(6) {
+            d = d.rotate_cw();
+        }
+        *center + d
+    }
+
+    /// Hexes at exactly distance radius, counter-clockwise starting from the E corner.
+    pub fn ring(&self, radius: u64) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let mut ret = Vec::with_capacity(6 * radius as usize);
+        let mut cur = self.step(HexDir::E, radius as i64);
+        for dir in HEX_DIRECTIONS {
+            // walk along the side that starts at the current corner
+            let side = dir.turn_left().turn_left();
+            for _ in 0..radius {
+                ret.push(cur);
+                cur = cur.step(side, 1);
+            }
+        }
+        ret
+    }
+
+    /// Hexes within distance radius, ordered by increasing distance.
+    pub fn spiral(&self, radius: u64) -> Vec<Hex> {
+        (0..=radius).flat_map(|k| self.ring(k)).collect()
+    }
+
+    /// Hexes on the straight line from self to other, inclusive.
+    pub fn line_to(&self, other: &Hex) -> Vec<Hex> {
+        let n = self.dist(other);
+        if n == 0 {
+            return vec![*self];
+        }
+        // nudge to break ties consistently when the line runs along hex edges
+        let (a, b) = (Cube::from(*self), Cube::from(*other));
+        let lerp = |x: i64, y: i64, t: f64, eps: f64| x as f64 + (y - x) as f64 * t + eps;
+        (0..=n)
+            .map(|i| {
+                let t = i as f64 / n as f64;
+                Hex::from(Cube::round(
+                    lerp(a.q, b.q, t, 1e-6),
+                    lerp(a.r, b.r, t, 2e-6),
+                    lerp(a.s, b.s, t, -3e-6),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Add for Hex {
+    type Output = Self;
+    fn add(self, o: Self) -> Self {
+        Hex::new(self.q + o.q, self.r + o.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, o: Self) {
+        self.q += o.q;
+        self.r += o.r;
+    }
+}
+
+impl Sub for Hex {
+    type Output = Self;
+    fn sub(self, o: Self) -> Self {
+        Hex::new(self.q - o.q, self.r - o.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Self;
+    fn mul(self, k: i64) -> Self {
+        Hex::new(self.q * k, self.r * k)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Hex::new(-self.q, -self.r)
+    }
+}
+
+/// Offset layout of the rows of a pointy-top hex map.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum OffsetLayout {
+    /// Odd rows are shifted half a hex to the right.
+    OddR,
+    /// Even rows are shifted half a hex to the right.
+    EvenR,
+}
+
+/// Dense hex map of h rows and w columns in offset coordinates.
+///
+/// HexMap<T> is indexed by Hex. Text uses one character per hex, separated
+/// by spaces, with shifted rows indented by one space:
+///
+/// ```text
+/// a b c
+///  d e f
+/// g h i
+/// ```
+#[derive(Debug, Clone)]
+pub struct HexMap<T> {
+    pub h: usize,
+    pub w: usize,
+    pub layout: OffsetLayout,
+    data: Vec<T>, // row-major in offset coordinates
+}
+
+impl<T> HexMap<T> {
+    /// Create map filled with constant.
+    pub fn new_constant(h: usize, w: usize, layout: OffsetLayout, t: T) -> Self
+    where
+        T: Clone,
+    {
+        HexMap {
+            h,
+            w,
+            layout,
+            data: vec![t; h * w],
+        }
+    }
+
+    // Create new map with same size.
+    pub fn same_size_with<S: Clone>(&self, val: S) -> HexMap<S> {
+        HexMap::new_constant(self.h, self.w, self.layout, val)
+    }
+
+    fn shifted(&self, row: i64) -> bool {
+        match self.layout {
+            OffsetLayout::OddR => row.rem_euclid(2) == 1,
+            OffsetLayout::EvenR => row.rem_euclid(2) == 0,
+        }
+    }
+
+    /// Convert hex to offset (col, row).
+    pub fn to_offset(&self, p: &Hex) -> (i64, i64) {
+        // col = q + floor(r / 2) for odd-r, col = q + ceil(r / 2) for even-r
+        let half = match self.layout {
+            OffsetLayout::OddR => p.r.div_euclid(2),
+            OffsetLayout::EvenR => (p.r + 1).div_euclid(2),
+        };
+        (p.q + half, p.r)
+    }
+
+    /// Convert offset (col, row) to hex.
+    pub fn from_offset(&self, col: i64, row: i64) -> Hex {
+        let half = match self.layout {
+            OffsetLayout::OddR => row.div_euclid(2),
+            OffsetLayout::EvenR => (row + 1).div_euclid(2),
+        };
+        Hex::new(col - half, row)
+    }
+
+    fn idx(&self, p: &Hex) -> Option<usize> {
+        let (col, row) = self.to_offset(p);
+        let inside = 0 <= col && col < self.w as i64 && 0 <= row && row < self.h as i64;
+        inside.then(|| row as usize * self.w + col as usize)
+    }
+
+    /// Check if hex is within map bounds.
+    pub fn contains(&self, p: &Hex) -> bool {
+        self.idx(p).is_some()
+    }
+
+    /// Get map element.
+    pub fn get(&self, p: &Hex) -> Option<&T> {
+        self.idx(p).map(|i| &self.data[i])
+    }
+
+    /// Get mutable map element.
+    pub fn get_mut(&mut self, p: &Hex) -> Option<&mut T> {
+        self.idx(p).map(|i| &mut self.data[i])
+    }
+
+    /// Iterate over adjacent hexes within map.
+    pub fn iter_neighbors(&self, p: &Hex) -> impl Iterator<Item = Hex> + '_ {
+        p.neighbors().filter(|q| self.contains(q))
+    }
+
+    /// Iterate over coordinates, row by row.
+    pub fn iter_coords(&self) -> impl Iterator<Item = Hex> + '_ {
+        (0..self.h as i64)
+            .flat_map(move |row| (0..self.w as i64).map(move |col| self.from_offset(col, row)))
+    }
+
+    /// Iterate over (coord, val) pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (Hex, &T)> {
+        self.iter_coords().zip(self.data.iter())
+    }
+
+    /// Iterate over flattened map.
+    pub fn iter_values(&self) -> impl Iterator<Item = &T> {
+        self.data.iter()
+    }
+}
+
+impl<T: GridTile> fmt::Display for HexMap<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.h {
+            if self.shifted(row as i64) {
+                write!(f, " ")?;
+            }
+            for col in 0..self.w {
+                if col > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", self.data[self.w * row + col].to_char())?;
+            }
+            if row + 1 < self.h {
+                writeln!(f)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error when parsing a hex map from text.
+///
+/// Lines are 1-based.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseHexMapError {
+    /// Tile or row length error shared with square maps.
+    Map(ParseMapError),
+    /// Row with the wrong indentation for the layout.
+    RowOffset { line: usize },
+}
+
+impl fmt::Display for ParseHexMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHexMapError::Map(err) => err.fmt(f),
+            ParseHexMapError::RowOffset { line } => {
+                write!(f, "line {line} has the wrong offset for a hex row")
+            }
+        }
+    }
+}
+
+impl Error for ParseHexMapError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ParseHexMapError::Map(err) => Some(err),
+            ParseHexMapError::RowOffset { .. } => None,
+        }
+    }
+}
+
+impl From<ParseMapError> for ParseHexMapError {
+    fn from(err: ParseMapError) -> Self {
+        ParseHexMapError::Map(err)
+    }
+}
+
+/// Parse hex map from text, ignoring leading and trailing empty lines.
+///
+/// The layout is OddR unless the first row is indented.
+impl<T: GridTile> FromStr for HexMap<T> {
+    type Err = ParseHexMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut data = Vec::new();
+        let mut h = 0;
+        let mut w = 0;
+        let mut layout = OffsetLayout::OddR;
+        for (i, line) in s.trim_matches(['\n', '\r']).lines().enumerate() {
+            let line = line.trim_end();
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            if i == 0 && indent == 1 {
+                layout = OffsetLayout::EvenR;
+            }
+            let shifted = (i % 2 == 1) == (layout == OffsetLayout::OddR);
+            if indent != shifted as usize {
+                return Err(ParseHexMapError::RowOffset { line: i + 1 });
+            }
+            let mut len = 0;
+            for (j, c) in line.chars().enumerate().skip(indent) {
+                let res = match (j - indent) % 2 {
+                    0 => T::from_char(c).map(|t| data.push(t)),
+                    _ if c == ' ' => continue,
+                    _ => Err("expected space between hexes".to_string()),
+                };
+                res.map_err(|msg| ParseMapError::InvalidTile {
+                    line: i + 1,
+                    col: j + 1,
+                    c,
+                    msg,
+                })?;
+                len += 1;
+            }
+            if i == 0 {
+                w = len;
+            } else if len != w {
+                return Err(ParseMapError::RaggedRow {
+                    line: i + 1,
+                    len,
+                    expected: w,
+                }
+                .into());
+            }
+            h += 1;
+        }
+        Ok(HexMap { h, w, layout, data })
+    }
+}
+
+/// Index with Hex.
+impl<T> Index<&Hex> for HexMap<T> {
+    type Output = T;
+    fn index(&self, p: &Hex) -> &Self::Output {
+        self.get(p).expect("hex outside map")
+    }
+}
+
+/// Mutable index with Hex.
+impl<T> IndexMut<&Hex> for HexMap<T> {
+    fn index_mut(&mut self, p: &Hex) -> &mut Self::Output {
+        self.get_mut(p).expect("hex outside map")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directions() {
+        for dir in HEX_DIRECTIONS {
+            assert_eq!(dir.to_hex().norm(), 1);
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.to_hex() + (-dir).to_hex(), Hex::ORIGIN);
+            assert_eq!(dir.to_hex().rotate_cw(), dir.turn_right().to_hex());
+            assert_eq!(dir.to_hex().rotate_ccw(), dir.turn_left().to_hex());
+        }
+        let path = HexDir::parse_path("nwwswee").unwrap();
+        let end = path.iter().fold(Hex::ORIGIN, |p, d| p.step(*d, 1));
+        assert_eq!(end, Hex::ORIGIN);
+        assert!(HexDir::parse_path("ex").is_err());
+        assert!(HexDir::parse_path("en").is_err());
+    }
+
+    #[test]
+    fn test_distance() {
+        let a = Hex::new(1, -3);
+        assert_eq!(a.norm(), 3);
+        assert_eq!(a.dist(&Hex::new(-2, 1)), 4);
+        for p in Hex::ORIGIN.spiral(3) {
+            for q in p.neighbors() {
+                assert_eq!(p.dist(&q), 1);
+            }
+        }
+        let c = Cube::from(a);
+        assert_eq!(c.q + c.r + c.s, 0);
+        assert_eq!(Hex::from(c), a);
+    }
+
+    #[test]
+    fn test_ring_spiral() {
+        let center = Hex::new(2, -1);
+        for r in 0..5 {
+            let ring = center.ring(r);
+            assert_eq!(ring.len(), if r == 0 { 1 } else { 6 * r as usize });
+            assert!(ring.iter().all(|p| p.dist(&center) == r));
+            for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                assert!(r == 0 || a.dist(b) == 1);
+            }
+        }
+        let spiral = center.spiral(3);
+        assert_eq!(spiral.len(), 1 + 3 * 3 * 4);
+        assert_eq!(spiral[0], center);
+    }
+
+    #[test]
+    fn test_line() {
+        let a = Hex::new(-2, 3);
+        for b in Hex::ORIGIN.spiral(4) {
+            let line = a.line_to(&b);
+            assert_eq!(line.len() as u64, a.dist(&b) + 1);
+            assert_eq!((line[0], *line.last().unwrap()), (a, b));
+            assert!(line.windows(2).all(|w| w[0].dist(&w[1]) == 1));
+        }
+        let line = Hex::ORIGIN.line_to(&Hex::new(3, 0));
+        assert_eq!(line, (0..4).map(|q| Hex::new(q, 0)).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let p = Hex::new(3, -1);
+        let center = Hex::new(1, 1);
+        let mut q = p;
+        for _ in 0..6 {
+            q = q.rotate_cw();
+            assert_eq!(q.norm(), p.norm());
+        }
+        assert_eq!(q, p);
+        assert_eq!(p.rotate_cw().rotate_ccw(), p);
+        assert_eq!(p.rotate_around(&center, 6), p);
+        assert_eq!(p.rotate_around(&center, -1), p.rotate_around(&center, 5));
+        assert_eq!(p.rotate_around(&center, 2).dist(&center), p.dist(&center));
+    }
+
+    #[test]
+    fn test_hex_map() {
+        let text = "a b c\n d e f\ng h i";
+        let map: HexMap<char> = text.parse().unwrap();
+        assert_eq!((map.h, map.w, map.layout), (3, 3, OffsetLayout::OddR));
+        assert_eq!(map.to_string(), text);
+
+        // e is at offset (1, 1) and touches b, c, d, f, h, i
+        let e = map.from_offset(1, 1);
+        assert_eq!(map[&e], 'e');
+        let mut adj = map.iter_neighbors(&e).map(|p| map[&p]).collect::<Vec<_>>();
+        adj.sort();
+        assert_eq!(adj, vec!['b', 'c', 'd', 'f', 'h', 'i']);
+        assert_eq!(map.iter_neighbors(&map.from_offset(0, 0)).count(), 2);
+
+        for (p, _) in map.iter() {
+            let (col, row) = map.to_offset(&p);
+            assert_eq!(map.from_offset(col, row), p);
+        }
+    }
+
+    #[test]
+    fn test_hex_map_even() {
+        let text = " a b\nc d\n e f";
+        let map: HexMap<char> = text.parse().unwrap();
+        assert_eq!(map.layout, OffsetLayout::EvenR);
+        assert_eq!(map.to_string(), text);
+        // d is at offset (1, 1) and touches a, b, c, e, f
+        let d = map.from_offset(1, 1);
+        assert_eq!(map.iter_neighbors(&d).count(), 5);
+    }
+
+    #[test]
+    fn test_hex_map_errors() {
+        let err = "a b\nc d".parse::<HexMap<char>>().unwrap_err();
+        assert_eq!(err, ParseHexMapError::RowOffset { line: 2 });
+        let err = "a b\n c d e".parse::<HexMap<char>>().unwrap_err();
+        assert!(matches!(
+            err,
+            ParseHexMapError::Map(ParseMapError::RaggedRow { line: 2, .. })
+        ));
+        let err = "ab".parse::<HexMap<char>>().unwrap_err();
+        assert!(matches!(
+            err,
+            ParseHexMapError::Map(ParseMapError::InvalidTile { col: 2, .. })
+        ));
+        assert_eq!(
+            err.to_string(),
+            "invalid tile 'b' at line 1, column 2: expected space between hexes"
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use crate::map2d::{Boundary, Map};
+use crate::vec2::Vec2i;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// RGB color.
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+pub const RED: Rgb = [255, 0, 0];
+pub const GREEN: Rgb = [0, 255, 0];
+pub const BLUE: Rgb = [0, 0, 255];
+
+/// Pixel type that can be written to a binary netpbm file.
+pub trait Pixel: Copy {
+    /// Netpbm magic number.
+    const MAGIC: &'static str;
+
+    /// Raw pixel bytes.
+    fn bytes(&self) -> &[u8];
+}
+
+/// Grayscale pixels, written as PGM.
+impl Pixel for u8 {
+    const MAGIC: &'static str = "P5";
+
+    fn bytes(&self) -> &[u8] {
+        std::slice::from_ref(self)
+    }
+}
+
+/// Color pixels, written as PPM.
+impl Pixel for Rgb {
+    const MAGIC: &'static str = "P6";
+
+    fn bytes(&self) -> &[u8] {
+        self
+    }
+}
+
+/// Image rendered from a map, one pixel per map cell.
+///
+/// Overlays are drawn at cell resolution, upscaling is applied when writing.
+#[derive(Debug, Clone)]
+pub struct Canvas<P: Pixel> {
+    pixels: Map<P>,
+    scale: usize,
+}
+
+impl<P: Pixel> Canvas<P> {
+    /// Render map using a palette that maps cells to pixels. The canvas is
+    /// always bounded, even if the source map wraps around.
+    pub fn from_map<T, F>(map: &Map<T>, palette: F) -> Self
+    where
+        F: Fn(&T) -> P,
+    {
+        let mut pixels = map.map_values(palette);
+        pixels.set_boundary(Boundary::Bounded);
+        Canvas { pixels, scale: 1 }
+    }
+
+    /// Draw each image pixel as a k x k block.
+    pub fn upscale(mut self, k: usize) -> Self {
+        assert!(k > 0);
+        self.scale = k;
+        self
+    }
+
+    /// Size (h, w) of the written image.
+    pub fn size(&self) -> (usize, usize) {
+        (self.pixels.h * self.scale, self.pixels.w * self.scale)
+    }
+
+    /// Pixel at map coordinate p.
+    pub fn get(&self, p: &Vec2i) -> Option<&P> {
+        self.pixels.get(p)
+    }
+
+    /// Color cells. Cells outside of the map are ignored.
+    pub fn highlight<I>(&mut self, cells: I, color: P) -> &mut Self
+    where
+        I: IntoIterator<Item = Vec2i>,
+    {
+        for p in cells {
+            if let Some(px) = self.pixels.get_mut(&p) {
+                *px = color;
+            }
+        }
+        self
+    }
+
+    /// Color a path through the given waypoints.
+    ///
+    /// Consecutive waypoints are connected with straight lines, which must be
+    /// horizontal, vertical or diagonal.
+    pub fn path(&mut self, waypoints: &[Vec2i], color: P) -> &mut Self {
+        if let Some(first) = waypoints.first() {
+            self.highlight([*first], color);
+        }
+        for pair in waypoints.windows(2) {
+            let (mut p, end) = (pair[0], pair[1]);
+            let d = end - p;
+            assert!(
+                d.x == 0 || d.y == 0 || d.x.abs() == d.y.abs(),
+                "path segment must be straight"
+            );
+            let step = Vec2i::new(d.x.signum(), d.y.signum());
+            while p != end {
+                p += step;
+                self.highlight([p], color);
+            }
+        }
+        self
+    }
+
+    /// Write image in binary netpbm format (PPM for Rgb, PGM for u8).
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let (h, w) = self.size();
+        write!(out, "{}\n{} {}\n255\n", P::MAGIC, w, h)?;
+        let mut line = Vec::with_capacity(w * 3);
+        for row in self.pixels.rows() {
+            line.clear();
+            for px in row {
+                for _ in 0..self.scale {
+                    line.extend_from_slice(px.bytes());
+                }
+            }
+            for _ in 0..self.scale {
+                out.write_all(&line)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write image to file.
+    pub fn save<Q: AsRef<Path>>(&self, path: Q) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ppm() {
+        let map: Map<bool> = "#.\n..".parse().unwrap();
+        let mut canvas = Canvas::from_map(&map, |b| if *b { WHITE } else { BLACK });
+        canvas.highlight([Vec2i::new(1, 1), Vec2i::new(5, 5)], RED);
+
+        let mut out = Vec::new();
+        canvas.write(&mut out).unwrap();
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(
+            &out[header.len()..],
+            &[255, 255, 255, 0, 0, 0, 0, 0, 0, 255, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_pgm_upscaled() {
+        let map: Map<u32> = "12\n34".parse().unwrap();
+        let canvas = Canvas::from_map(&map, |d| *d as u8).upscale(2);
+        assert_eq!(canvas.size(), (4, 4));
+
+        let mut out = Vec::new();
+        canvas.write(&mut out).unwrap();
+        let header = b"P5\n4 4\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(
+            &out[header.len()..],
+            &[1, 1, 2, 2, 1, 1, 2, 2, 3, 3, 4, 4, 3, 3, 4, 4]
+        );
+    }
+
+    #[test]
+    fn test_path() {
+        let map = Map::<u8>::new(4, 4);
+        let mut canvas = Canvas::from_map(&map, |_| 0u8);
+        canvas.path(&[Vec2i::new(0, 0), Vec2i::new(2, 0), Vec2i::new(0, 2)], 9);
+        let drawn = map
+            .iter_coords()
+            .filter(|p| canvas.get(p) == Some(&9))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            drawn,
+            vec![
+                Vec2i::new(0, 0),
+                Vec2i::new(1, 0),
+                Vec2i::new(2, 0),
+                Vec2i::new(1, 1),
+                Vec2i::new(0, 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_toroidal_source() {
+        let map = Map::<u8>::new(3, 3).toroidal();
+        let mut canvas = Canvas::from_map(&map, |_| 0u8);
+        canvas.highlight([Vec2i::new(3, 0), Vec2i::new(-1, -1)], 9);
+        canvas.path(&[Vec2i::new(2, 1), Vec2i::new(4, 1)], 7);
+        let drawn = map
+            .iter_coords()
+            .filter_map(|p| canvas.get(&p).filter(|&&c| c != 0).map(|&c| (p, c)))
+            .collect::<Vec<_>>();
+        assert_eq!(drawn, vec![(Vec2i::new(2, 1), 7)]);
+    }
+}
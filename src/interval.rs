@@ -0,0 +1,569 @@
+use crate::vec2::Scalar;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+
+/// Set of values stored as disjoint half-open intervals.
+///
+/// Touching intervals are merged, so the representation is unique. Insertion,
+/// removal, membership and first-fit gap queries are logarithmic in the number of
+/// intervals, plus the number of intervals that get merged or split.
+#[derive(Clone)]
+pub struct IntervalSet<T: Scalar + Ord> {
+    map: BTreeMap<T, T>, // start -> end
+    gaps: GapTree<T>,    // gaps between consecutive intervals
+    len: T,
+}
+
+impl<T: Scalar + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        IntervalSet {
+            map: BTreeMap::new(),
+            gaps: GapTree::new(),
+            len: T::ZERO,
+        }
+    }
+
+    /// Check if set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Number of disjoint intervals.
+    pub fn num_intervals(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Total length of all intervals.
+    pub fn len(&self) -> T {
+        self.len
+    }
+
+    /// Iterate over intervals in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.map.iter().map(|(s, e)| *s..*e)
+    }
+
+    /// Check if x is in the set.
+    pub fn contains(&self, x: &T) -> bool {
+        self.interval_of(x).is_some()
+    }
+
+    /// Interval that contains x.
+    pub fn interval_of(&self, x: &T) -> Option<Range<T>> {
+        self.map
+            .range(..=*x)
+            .next_back()
+            .filter(|(_, e)| x < *e)
+            .map(|(s, e)| *s..*e)
+    }
+
+    /// Add all values in range.
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = (range.start, range.end);
+        if let Some((s, e)) = self.map.range(..=start).next_back() {
+            if *e >= start {
+                start = *s;
+            }
+        }
+        while let Some((s, _)) = self.map.range(start..=end).next() {
+            end = end.max(self.drop_interval(*s));
+        }
+        self.add_interval(start, end);
+    }
+
+    /// Remove all values in range.
+    pub fn remove(&mut self, range: Range<T>) {
+        if range.is_empty() {
+            return;
+        }
+        if let Some((s, e)) = self.map.range(..range.start).next_back() {
+            let (s, e) = (*s, *e);
+            if e > range.start {
+                self.drop_interval(s);
+                self.add_interval(s, range.start);
+                if e > range.end {
+                    self.add_interval(range.end, e);
+                }
+            }
+        }
+        while let Some((s, _)) = self.map.range(range.clone()).next() {
+            let e = self.drop_interval(*s);
+            if e > range.end {
+                self.add_interval(range.end, e);
+            }
+        }
+    }
+
+    /// Values in self or other.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut ret = self.clone();
+        other.iter().for_each(|r| ret.insert(r));
+        ret
+    }
+
+    /// Values in self but not in other.
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ret = self.clone();
+        other.iter().for_each(|r| ret.remove(r));
+        ret
+    }
+
+    /// Values in both self and other.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ret = Self::new();
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let (start, end) = (x.start.max(y.start), x.end.min(y.end));
+            if start < end {
+                // pieces never touch, since both inputs have no touching intervals
+                ret.add_interval(start, end);
+            }
+            if x.end < y.end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        ret
+    }
+
+    /// Maximal ranges inside `within` that are not in the set, in increasing order.
+    pub fn gaps(&self, within: Range<T>) -> impl Iterator<Item = Range<T>> + '_ {
+        let mut cursor = match self.interval_of(&within.start) {
+            Some(r) => r.end,
+            None => within.start,
+        };
+        let end = within.end;
+        self.map
+            .range(within.start..)
+            .map(|(s, e)| (*s, *e))
+            .take_while(move |(s, _)| *s < end)
+            .chain([(end, end)])
+            .filter_map(move |(s, e)| {
+                let gap = cursor..s.min(end);
+                cursor = cursor.max(e);
+                (!gap.is_empty()).then_some(gap)
+            })
+    }
+
+    /// Start of the first gap of length at least k that lies completely inside `within`.
+    ///
+    /// An empty gap fits anywhere, so k = 0 returns `within.start`.
+    pub fn first_gap(&self, k: T, within: Range<T>) -> Option<T> {
+        if k <= T::ZERO {
+            return Some(within.start);
+        }
+        let fits = |s: T| (s + k <= within.end).then_some(s);
+        // the gap at the start of `within` may be clipped or unbounded
+        let cursor = match self.interval_of(&within.start) {
+            Some(r) => r.end,
+            None => within.start,
+        };
+        match self.map.range(cursor..).next() {
+            Some((next, _)) if *next - cursor < k => {}
+            _ => return fits(cursor),
+        }
+        // gaps between intervals after the cursor, then the unbounded gap after the last
+        match self.gaps.first_fit(cursor, k) {
+            Some(s) => fits(s),
+            None => fits(*self.map.last_key_value()?.1),
+        }
+    }
+
+    /// Map values through piecewise shifts.
+    ///
+    /// Each shift (src, dst) moves the values in src so that src.start lands on dst.
+    /// Values that are not in any source range are kept as they are. Source ranges
+    /// should be disjoint.
+    pub fn map_shifts(&self, shifts: &[(Range<T>, T)]) -> Self {
+        let mut ret = self.clone();
+        let mut moved = Vec::new();
+        for (src, dst) in shifts {
+            ret.remove(src.clone());
+            let src_set = Self::from_iter([src.clone()]);
+            for r in self.intersection(&src_set).iter() {
+                moved.push(*dst + (r.start - src.start)..*dst + (r.end - src.start));
+            }
+        }
+        moved.into_iter().for_each(|r| ret.insert(r));
+        ret
+    }
+
+    /// Add interval that neither overlaps nor touches any existing interval.
+    fn add_interval(&mut self, s: T, e: T) {
+        let prev = self.map.range(..s).next_back().map(|(_, e)| *e);
+        let next = self.map.range(s..).next().map(|(s, _)| *s);
+        match (prev, next) {
+            (Some(pe), Some(_)) => self.gaps.set_end(pe, s),
+            (Some(pe), None) => self.gaps.insert(pe, s),
+            _ => {}
+        }
+        if let Some(ns) = next {
+            self.gaps.insert(e, ns);
+        }
+        self.map.insert(s, e);
+        self.len += e - s;
+    }
+
+    /// Remove the interval starting at s and return its end.
+    fn drop_interval(&mut self, s: T) -> T {
+        let e = self.map.remove(&s).unwrap();
+        let prev = self.map.range(..s).next_back().map(|(_, e)| *e);
+        let next = self.map.range(s..).next().map(|(s, _)| *s);
+        if next.is_some() {
+            self.gaps.remove(e);
+        }
+        match (prev, next) {
+            (Some(pe), Some(ns)) => self.gaps.set_end(pe, ns),
+            (Some(pe), None) => self.gaps.remove(pe),
+            _ => {}
+        }
+        self.len -= e - s;
+        e
+    }
+}
+
+impl<T: Scalar + Ord> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Scalar + Ord> PartialEq for IntervalSet<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.map == other.map
+    }
+}
+
+impl<T: Scalar + Ord> Eq for IntervalSet<T> {}
+
+impl<T: Scalar + Ord + fmt::Debug> fmt::Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<T: Scalar + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        let mut ret = Self::new();
+        iter.into_iter().for_each(|r| ret.insert(r));
+        ret
+    }
+}
+
+/// Treap of gaps keyed by start, where every node knows the longest gap in its subtree.
+#[derive(Debug, Clone)]
+struct GapTree<T> {
+    nodes: Vec<GapNode<T>>,
+    free: Vec<usize>,
+    root: Option<usize>,
+    seed: u64,
+}
+
+#[derive(Debug, Clone)]
+struct GapNode<T> {
+    start: T,
+    end: T,
+    longest: T,
+    prio: u64,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl<T: Scalar + Ord> GapTree<T> {
+    fn new() -> Self {
+        GapTree {
+            nodes: vec![],
+            free: vec![],
+            root: None,
+            seed: 0x2545f4914f6cdd1d,
+        }
+    }
+
+    fn insert(&mut self, start: T, end: T) {
+        // xorshift for the heap priorities
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        let node = GapNode {
+            start,
+            end,
+            longest: end - start,
+            prio: self.seed,
+            left: None,
+            right: None,
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+        self.root = self.insert_rec(self.root, id);
+    }
+
+    fn insert_rec(&mut self, t: Option<usize>, id: usize) -> Option<usize> {
+        let Some(t) = t else {
+            return Some(id);
+        };
+        let start = self.nodes[id].start;
+        if self.nodes[t].prio < self.nodes[id].prio {
+            // new node becomes the root of this subtree
+            let (l, r) = self.split(Some(t), start);
+            self.nodes[id].left = l;
+            self.nodes[id].right = r;
+            self.pull(id);
+            return Some(id);
+        }
+        if start < self.nodes[t].start {
+            self.nodes[t].left = self.insert_rec(self.nodes[t].left, id);
+        } else {
+            self.nodes[t].right = self.insert_rec(self.nodes[t].right, id);
+        }
+        self.pull(t);
+        Some(t)
+    }
+
+    fn remove(&mut self, start: T) {
+        self.root = self.remove_rec(self.root, start);
+    }
+
+    fn remove_rec(&mut self, t: Option<usize>, start: T) -> Option<usize> {
+        let id = t?;
+        let node = &self.nodes[id];
+        if start == node.start {
+            self.free.push(id);
+            return self.merge(node.left, node.right);
+        }
+        if start < node.start {
+            self.nodes[id].left = self.remove_rec(node.left, start);
+        } else {
+            self.nodes[id].right = self.remove_rec(node.right, start);
+        }
+        self.pull(id);
+        Some(id)
+    }
+
+    /// Change the end of the gap that starts at start.
+    fn set_end(&mut self, start: T, end: T) {
+        self.set_end_rec(self.root, start, end);
+    }
+
+    fn set_end_rec(&mut self, t: Option<usize>, start: T, end: T) {
+        let Some(id) = t else {
+            return;
+        };
+        let node = &self.nodes[id];
+        match start.cmp(&node.start) {
+            Ordering::Less => self.set_end_rec(node.left, start, end),
+            Ordering::Greater => self.set_end_rec(node.right, start, end),
+            Ordering::Equal => self.nodes[id].end = end,
+        }
+        self.pull(id);
+    }
+
+    /// Start of the leftmost gap with start >= lo and length >= k.
+    fn first_fit(&self, lo: T, k: T) -> Option<T> {
+        self.first_fit_rec(self.root, lo, k)
+    }
+
+    fn first_fit_rec(&self, t: Option<usize>, lo: T, k: T) -> Option<T> {
+        let node = &self.nodes[t?];
+        if node.longest < k {
+            return None;
+        }
+        if node.start < lo {
+            return self.first_fit_rec(node.right, lo, k);
+        }
+        self.first_fit_rec(node.left, lo, k)
+            .or_else(|| (node.end - node.start >= k).then_some(node.start))
+            .or_else(|| self.first_fit_rec(node.right, lo, k))
+    }
+
+    fn pull(&mut self, id: usize) {
+        let node = &self.nodes[id];
+        let mut longest = node.end - node.start;
+        for child in [node.left, node.right].into_iter().flatten() {
+            longest = longest.max(self.nodes[child].longest);
+        }
+        self.nodes[id].longest = longest;
+    }
+
+    /// Split into nodes with start < key and the rest.
+    fn split(&mut self, t: Option<usize>, key: T) -> (Option<usize>, Option<usize>) {
+        let Some(id) = t else {
+            return (None, None);
+        };
+        if self.nodes[id].start < key {
+            let (l, r) = self.split(self.nodes[id].right, key);
+            self.nodes[id].right = l;
+            self.pull(id);
+            (Some(id), r)
+        } else {
+            let (l, r) = self.split(self.nodes[id].left, key);
+            self.nodes[id].left = r;
+            self.pull(id);
+            (l, Some(id))
+        }
+    }
+
+    /// Merge two treaps where all keys in a are smaller than those in b.
+    fn merge(&mut self, a: Option<usize>, b: Option<usize>) -> Option<usize> {
+        let (Some(x), Some(y)) = (a, b) else {
+            return a.or(b);
+        };
+        if self.nodes[x].prio > self.nodes[y].prio {
+            self.nodes[x].right = self.merge(self.nodes[x].right, Some(y));
+            self.pull(x);
+            Some(x)
+        } else {
+            self.nodes[y].left = self.merge(Some(x), self.nodes[y].left);
+            self.pull(y);
+            Some(y)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    /// In-order contents of the gap tree, checking the longest-gap summaries.
+    fn tree_gaps(tree: &GapTree<i64>, t: Option<usize>, out: &mut Vec<Range<i64>>) -> i64 {
+        let Some(id) = t else {
+            return 0;
+        };
+        let node = &tree.nodes[id];
+        let l = tree_gaps(tree, node.left, out);
+        out.push(node.start..node.end);
+        let r = tree_gaps(tree, node.right, out);
+        assert_eq!(node.longest, l.max(r).max(node.end - node.start));
+        node.longest
+    }
+
+    fn to_bits(set: &IntervalSet<i64>, n: i64) -> Vec<bool> {
+        (0..n).map(|x| set.contains(&x)).collect()
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut set = IntervalSet::new();
+        set.insert(1..3);
+        set.insert(5..7);
+        assert_eq!(set.num_intervals(), 2);
+        set.insert(3..5); // touching intervals merge
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..7]);
+        set.remove(2..4);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1..2, 4..7]);
+        assert_eq!(set.len(), 4);
+        assert!(set.contains(&1));
+        assert!(!set.contains(&2));
+        assert_eq!(set.interval_of(&5), Some(4..7));
+        set.remove(0..10);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_random_against_bits() {
+        let n = 150;
+        let mut set = IntervalSet::new();
+        let mut bits = vec![false; n as usize];
+        let mut rng = Lcg::new(5);
+        for _ in 0..1000 {
+            let seed = rng.next_u64();
+            let a = (seed >> 33) as i64 % n;
+            let b = (a + 1 + (seed >> 45) as i64 % 8).min(n);
+            let r = a..b;
+            let add = !(seed >> 20).is_multiple_of(3);
+            if add {
+                set.insert(r.clone());
+            } else {
+                set.remove(r.clone());
+            }
+            for x in r {
+                bits[x as usize] = add;
+            }
+            assert_eq!(to_bits(&set, n), bits);
+            assert_eq!(set.len(), bits.iter().filter(|b| **b).count() as i64);
+            // intervals are disjoint and not touching
+            let iv: Vec<_> = set.iter().collect();
+            assert!(iv.windows(2).all(|w| w[0].end < w[1].start));
+            let mut gaps = vec![];
+            tree_gaps(&set.gaps, set.gaps.root, &mut gaps);
+            assert_eq!(
+                gaps,
+                iv.windows(2)
+                    .map(|w| w[0].end..w[1].start)
+                    .collect::<Vec<_>>()
+            );
+            // first-fit queries agree with scanning the gaps
+            for k in 1..6 {
+                for within in [0..n, a..n, 0..b, a.min(b)..a.max(b)] {
+                    let expected = set
+                        .gaps(within.clone())
+                        .find(|g| g.end - g.start >= k)
+                        .map(|g| g.start);
+                    assert_eq!(set.first_gap(k, within), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: IntervalSet<i64> = [0..5, 10..15].into_iter().collect();
+        let b: IntervalSet<i64> = [3..12, 14..20].into_iter().collect();
+        let n = 25;
+        let (ba, bb) = (to_bits(&a, n), to_bits(&b, n));
+        let zip = |f: fn(bool, bool) -> bool| -> Vec<bool> {
+            ba.iter().zip(&bb).map(|(x, y)| f(*x, *y)).collect()
+        };
+        assert_eq!(to_bits(&a.union(&b), n), zip(|x, y| x || y));
+        assert_eq!(to_bits(&a.intersection(&b), n), zip(|x, y| x && y));
+        assert_eq!(to_bits(&a.difference(&b), n), zip(|x, y| x && !y));
+        assert_eq!(
+            a.intersection(&b).iter().collect::<Vec<_>>(),
+            vec![3..5, 10..12, 14..15]
+        );
+    }
+
+    #[test]
+    fn test_gaps() {
+        let set: IntervalSet<u32> = [2..4, 5..6, 9..12].into_iter().collect();
+        assert_eq!(
+            set.gaps(0..20).collect::<Vec<_>>(),
+            vec![0..2, 4..5, 6..9, 12..20]
+        );
+        assert_eq!(set.gaps(3..10).collect::<Vec<_>>(), vec![4..5, 6..9]);
+        assert_eq!(set.gaps(2..4).count(), 0);
+        assert_eq!(set.first_gap(2, 2..20), Some(6));
+        assert_eq!(set.first_gap(3, 0..8), None);
+        assert_eq!(set.first_gap(3, 0..9), Some(6));
+        assert_eq!(set.first_gap(1, 0..20), Some(0));
+        assert_eq!(set.first_gap(1, 2..4), None);
+        assert_eq!(set.first_gap(0, 2..4), Some(2));
+        assert_eq!(set.first_gap(0, 6..9), Some(6));
+    }
+
+    #[test]
+    fn test_map_shifts() {
+        // seed-to-soil style mapping
+        let seeds: IntervalSet<i64> = [79..93, 55..68].into_iter().collect();
+        let soil = seeds.map_shifts(&[(98..100, 50), (50..98, 52)]);
+        assert_eq!(soil.iter().collect::<Vec<_>>(), vec![57..70, 81..95]);
+
+        let set: IntervalSet<i64> = std::iter::once(0..10).collect();
+        let shifted = set.map_shifts(&[(2..4, 20), (8..12, 0)]);
+        assert_eq!(shifted.iter().collect::<Vec<_>>(), vec![0..2, 4..8, 20..22]);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::map2d::{BitMap, Map};
+use crate::vec2::{Dir, Vec2i, DIRECTIONS};
+
+const NONE: u32 = u32::MAX;
+
+/// Precomputed "next blocker" lookup for every cell and direction.
+///
+/// For each cell p and direction d, stores the first blocked cell
+/// strictly after p when walking in direction d. Updating a single cell
+/// is O(h + w).
+#[derive(Debug, Clone)]
+pub struct JumpTable {
+    blocked: BitMap,
+    jumps: Map<[u32; 4]>, // linear index of next blocker, NONE if ray leaves map
+}
+
+impl JumpTable {
+    /// Build table from the cells of a map that satisfy `is_blocked`.
+    pub fn from_map<T, F>(map: &Map<T>, is_blocked: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut ret = JumpTable {
+            blocked: BitMap::from_map(map, is_blocked),
+            jumps: map.same_size_with([NONE; 4]),
+        };
+        for dir in DIRECTIONS {
+            // walk each line against dir, keeping track of the last seen blocker
+            let line_starts: Vec<Vec2i> = match dir {
+                Dir::N => (0..map.w).map(|x| Vec2i::new(x as i64, 0)).collect(),
+                Dir::S => (0..map.w)
+                    .map(|x| Vec2i::new(x as i64, map.h as i64 - 1))
+                    .collect(),
+                Dir::W => (0..map.h).map(|y| Vec2i::new(0, y as i64)).collect(),
+                Dir::E => (0..map.h)
+                    .map(|y| Vec2i::new(map.w as i64 - 1, y as i64))
+                    .collect(),
+            };
+            for mut p in line_starts {
+                let mut last = NONE;
+                while ret.blocked.contains(&p) {
+                    ret.jumps[&p][dir as usize] = last;
+                    if ret.blocked.get(&p) == Some(true) {
+                        last = p.linear_idx(map.w) as u32;
+                    }
+                    p = p.step(-dir, 1);
+                }
+            }
+        }
+        ret
+    }
+
+    fn to_coord(&self, idx: u32) -> Option<Vec2i> {
+        let w = self.jumps.w as u32;
+        (idx != NONE).then(|| Vec2i::new((idx % w) as i64, (idx / w) as i64))
+    }
+
+    /// Check if cell is blocked.
+    pub fn is_blocked(&self, p: &Vec2i) -> bool {
+        self.blocked.get(p) == Some(true)
+    }
+
+    /// First blocked cell after p in direction dir, None if there is none.
+    pub fn next_blocker(&self, p: &Vec2i, dir: Dir) -> Option<Vec2i> {
+        self.to_coord(self.jumps[p][dir as usize])
+    }
+
+    /// Like next_blocker, but with `extra` treated as an additional blocked cell.
+    pub fn next_blocker_with(&self, p: &Vec2i, dir: Dir, extra: &Vec2i) -> Option<Vec2i> {
+        let unit = Vec2i::new(0, 0).step(dir, 1);
+        let dist = |q: &Vec2i| (q.x - p.x) * unit.x + (q.y - p.y) * unit.y;
+        let d = *extra - *p;
+        let extra_on_ray = d.x * unit.y == d.y * unit.x && dist(extra) > 0;
+        match self.next_blocker(p, dir) {
+            Some(b) if !extra_on_ray || dist(&b) < dist(extra) => Some(b),
+            _ if extra_on_ray => Some(*extra),
+            b => b,
+        }
+    }
+
+    /// Last free cell before the next blocker, and the blocker (None if leaving the map).
+    pub fn cast_ray(&self, p: &Vec2i, dir: Dir) -> (Vec2i, Option<Vec2i>) {
+        match self.next_blocker(p, dir) {
+            Some(b) => (b.step(-dir, 1), Some(b)),
+            None => {
+                let (h, w) = (self.jumps.h as i64 - 1, self.jumps.w as i64 - 1);
+                let edge = match dir {
+                    Dir::N => Vec2i::new(p.x, 0),
+                    Dir::E => Vec2i::new(w, p.y),
+                    Dir::S => Vec2i::new(p.x, h),
+                    Dir::W => Vec2i::new(0, p.y),
+                };
+                (edge, None)
+            }
+        }
+    }
+
+    /// Mark cell as blocked or free and update affected jumps.
+    pub fn set_blocked(&mut self, p: &Vec2i, blocked: bool) {
+        if self.is_blocked(p) == blocked {
+            return;
+        }
+        self.blocked.set(p, blocked);
+        for dir in DIRECTIONS {
+            // cells before p along dir now see p, or what lies beyond p
+            let target = match blocked {
+                true => p.linear_idx(self.jumps.w) as u32,
+                false => self.jumps[p][dir as usize],
+            };
+            let mut q = p.step(-dir, 1);
+            while self.blocked.contains(&q) {
+                self.jumps[&q][dir as usize] = target;
+                if self.is_blocked(&q) {
+                    break;
+                }
+                q = q.step(-dir, 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    fn brute_force(map: &Map<bool>, p: &Vec2i, dir: Dir) -> Option<Vec2i> {
+        map.cast_ray(p, dir, |b| *b).1
+    }
+
+    fn check_all(table: &JumpTable, map: &Map<bool>) {
+        for p in map.iter_coords() {
+            for dir in DIRECTIONS {
+                assert_eq!(table.next_blocker(&p, dir), brute_force(map, &p, dir));
+            }
+        }
+    }
+
+    #[test]
+    fn test_jump_table() {
+        let map: Map<bool> = "..#..\n.....\n#...#\n..#..".parse().unwrap();
+        let table = JumpTable::from_map(&map, |b| *b);
+        check_all(&table, &map);
+
+        let p = Vec2i::new(2, 2);
+        assert_eq!(table.next_blocker(&p, Dir::N), Some(Vec2i::new(2, 0)));
+        assert_eq!(
+            table.cast_ray(&p, Dir::N),
+            (Vec2i::new(2, 1), Some(Vec2i::new(2, 0)))
+        );
+        assert_eq!(
+            table.cast_ray(&Vec2i::new(1, 1), Dir::E),
+            (Vec2i::new(4, 1), None)
+        );
+    }
+
+    #[test]
+    fn test_jump_table_update() {
+        let mut map: Map<bool> = "..#..\n.....\n#...#\n..#..".parse().unwrap();
+        let mut table = JumpTable::from_map(&map, |b| *b);
+
+        let mut rng = Lcg::new(7);
+        for _ in 0..100 {
+            let i = rng.below(map.h * map.w);
+            let p = Vec2i::new((i % map.w) as i64, (i / map.w) as i64);
+            map[&p] = !map[&p];
+            table.set_blocked(&p, map[&p]);
+            check_all(&table, &map);
+        }
+    }
+
+    #[test]
+    fn test_next_blocker_with() {
+        let map: Map<bool> = "..#..\n.....\n#...#\n..#..".parse().unwrap();
+        let table = JumpTable::from_map(&map, |b| *b);
+        for p in map.iter_coords() {
+            for extra in map.iter_coords() {
+                let mut with_extra = map.clone();
+                with_extra[&extra] = true;
+                for dir in DIRECTIONS {
+                    assert_eq!(
+                        table.next_blocker_with(&p, dir, &extra),
+                        brute_force(&with_extra, &p, dir)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -2,12 +2,25 @@ use std::fmt;
 use std::path::PathBuf;
 
 pub mod container;
+pub mod distance;
+pub mod geometry;
+pub mod grid;
 pub mod hash;
 pub mod heap;
+pub mod hex;
+pub mod image;
+pub mod interval;
+pub mod jump_table;
 pub mod map2d;
 pub mod math;
 pub mod parsing;
+pub mod range_query;
+pub mod recorder;
+pub mod rect;
 pub mod solutions;
+pub mod sssp;
+#[cfg(test)]
+mod testutil;
 pub mod trie;
 pub mod vec2;
 
@@ -1,4 +1,4 @@
-use aoc2024::{get_default_data_path, Answer};
+use aoc2024::{get_default_data_path, recorder, Answer};
 use clap::{ArgAction, Parser, Subcommand};
 use std::fs;
 use std::hint::black_box;
@@ -29,6 +29,12 @@ struct RunArgs {
     pub input: Option<PathBuf>,
     #[arg(long, short, action=ArgAction::SetTrue)]
     pub benchmark: bool,
+    /// Write recorded frames as PPM images to this directory (requires feature "visualize").
+    #[arg(long, value_name = "DIR", default_value=None)]
+    pub visualize: Option<PathBuf>,
+    /// Play recorded frames in the terminal (requires feature "visualize").
+    #[arg(long, action=ArgAction::SetTrue)]
+    pub animate: bool,
 }
 
 #[derive(Parser)]
@@ -62,6 +68,38 @@ fn part_run(f: impl Fn(&str) -> Answer, input: &str, benchmark: bool) -> (Answer
     }
 }
 
+fn part_run_visualized(
+    f: impl Fn(&str) -> Answer,
+    input: &str,
+    args: &RunArgs,
+    part: &str,
+) -> Result<(Answer, Duration, u128), Box<dyn std::error::Error>> {
+    if args.visualize.is_none() && !args.animate {
+        return Ok(part_run(f, input, args.benchmark));
+    }
+    if !cfg!(feature = "visualize") {
+        return Err("--visualize and --animate require building with --features visualize".into());
+    }
+    if args.benchmark {
+        return Err("--benchmark cannot be combined with --visualize or --animate".into());
+    }
+
+    recorder::start();
+    let t0 = Instant::now();
+    let answer = f(input);
+    let dt = t0.elapsed();
+    let recording = recorder::finish().unwrap_or_default();
+
+    if args.animate {
+        recording.play(&mut std::io::stdout(), Duration::from_millis(50))?;
+    }
+    if let Some(dir) = &args.visualize {
+        recording.save_ppm_sequence(dir.join(part), 4)?;
+        println!("Wrote {} frames to {:?}", recording.len(), dir.join(part));
+    }
+    Ok((answer, dt, 1))
+}
+
 fn main_run(args: &RunArgs) -> Result<Duration, Box<dyn std::error::Error>> {
     let (part_a, part_b) = aoc2024::solutions::ALL
         .get(args.day.saturating_sub(1) as usize)
@@ -74,11 +112,11 @@ fn main_run(args: &RunArgs) -> Result<Duration, Box<dyn std::error::Error>> {
     let data =
         fs::read_to_string(&path).unwrap_or_else(|_| panic!("Couldn't open file {:?}", path));
 
-    let (out_a, dt_a, n_a) = part_run(part_a, data.as_str(), args.benchmark);
+    let (out_a, dt_a, n_a) = part_run_visualized(part_a, data.as_str(), args, "a")?;
     let dt_a_ms = dt_a.as_secs_f64() * 1e3;
     println!("Part a: {out_a:<16} {dt_a_ms:>10.3}ms [N={n_a}]");
 
-    let (out_b, dt_b, n_b) = part_run(part_b, data.as_str(), args.benchmark);
+    let (out_b, dt_b, n_b) = part_run_visualized(part_b, data.as_str(), args, "b")?;
     let dt_b_ms = dt_b.as_secs_f64() * 1e3;
     println!("Part b: {out_b:<16} {dt_b_ms:>10.3}ms [N={n_b}]");
 
@@ -92,6 +130,8 @@ fn main_run_all(args: &RunAllArgs) -> Result<(), Box<dyn std::error::Error>> {
             day: day as u32,
             input: None,
             benchmark: args.benchmark,
+            visualize: None,
+            animate: false,
         };
         println!("Running day {day:02}");
         let day_duration = main_run(&args)?;
@@ -1,10 +1,130 @@
-use crate::vec2::{Dir, Vec2i};
+use crate::container::DisjointSet;
+use crate::grid::Grid;
+use crate::hash::{FxHashMap, FxHashMapBuilder};
+use crate::rect::Rect;
+use crate::vec2::{Dir, Vec2i, DIRECTIONS};
+use bitvec::prelude::*;
 use std::{
+    error::Error,
     fmt,
-    ops::{Index, IndexMut},
-    str::Lines,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Index, IndexMut},
+    str::{FromStr, Lines},
 };
 
+/// Orthogonal neighbor offsets in N, E, S, W order.
+const OFFSETS_4: [Vec2i; 4] = [
+    Vec2i::new(0, -1),
+    Vec2i::new(1, 0),
+    Vec2i::new(0, 1),
+    Vec2i::new(-1, 0),
+];
+
+/// Orthogonal and diagonal neighbor offsets, clockwise from N.
+const OFFSETS_8: [Vec2i; 8] = [
+    Vec2i::new(0, -1),
+    Vec2i::new(1, -1),
+    Vec2i::new(1, 0),
+    Vec2i::new(1, 1),
+    Vec2i::new(0, 1),
+    Vec2i::new(-1, 1),
+    Vec2i::new(-1, 0),
+    Vec2i::new(-1, -1),
+];
+
+/// How coordinates outside of [0, w)x[0, h) are treated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Boundary {
+    /// Coordinates outside the map are invalid.
+    #[default]
+    Bounded,
+    /// Coordinates wrap around in both directions.
+    Toroidal,
+}
+
+/// Map cell that has a single-character text representation.
+pub trait GridTile: Sized {
+    /// Parse tile from character.
+    fn from_char(c: char) -> Result<Self, String>;
+
+    /// Character representation of tile.
+    fn to_char(&self) -> char;
+}
+
+impl GridTile for char {
+    fn from_char(c: char) -> Result<Self, String> {
+        Ok(c)
+    }
+
+    fn to_char(&self) -> char {
+        *self
+    }
+}
+
+/// Booleans are represented as '#' (true) and '.' (false).
+impl GridTile for bool {
+    fn from_char(c: char) -> Result<Self, String> {
+        match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(format!("Invalid bool {c}")),
+        }
+    }
+
+    fn to_char(&self) -> char {
+        if *self {
+            '#'
+        } else {
+            '.'
+        }
+    }
+}
+
+/// Single decimal digits.
+impl GridTile for u32 {
+    fn from_char(c: char) -> Result<Self, String> {
+        c.to_digit(10).ok_or_else(|| format!("Invalid digit {c}"))
+    }
+
+    fn to_char(&self) -> char {
+        char::from_digit(*self, 10).unwrap_or('?')
+    }
+}
+
+/// Error when parsing a map from text.
+///
+/// Lines and columns are 1-based.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseMapError {
+    InvalidTile {
+        line: usize,
+        col: usize,
+        c: char,
+        msg: String,
+    },
+    RaggedRow {
+        line: usize,
+        len: usize,
+        expected: usize,
+    },
+}
+
+impl fmt::Display for ParseMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseMapError::InvalidTile { line, col, c, msg } => {
+                write!(f, "invalid tile {c:?} at line {line}, column {col}: {msg}")
+            }
+            ParseMapError::RaggedRow {
+                line,
+                len,
+                expected,
+            } => write!(f, "line {line} has length {len}, expected {expected}"),
+        }
+    }
+}
+
+impl Error for ParseMapError {}
+
 /// 2D map type.
 ///
 /// Map<T> is indexed by Vec2i using "image" coordinates, i.e.
@@ -14,6 +134,7 @@ pub struct Map<T> {
     pub h: usize,
     pub w: usize,
     data: Vec<T>, // row-major
+    boundary: Boundary,
 }
 
 impl<T> Map<T> {
@@ -26,6 +147,7 @@ impl<T> Map<T> {
             h,
             w,
             data: vec![T::default(); h * w],
+            boundary: Boundary::Bounded,
         }
     }
 
@@ -38,6 +160,7 @@ impl<T> Map<T> {
             h,
             w,
             data: vec![t; h * w],
+            boundary: Boundary::Bounded,
         }
     }
 
@@ -58,7 +181,12 @@ impl<T> Map<T> {
                 data.push(x);
             }
         }
-        Map { h, w, data }
+        Map {
+            h,
+            w,
+            data,
+            boundary: Boundary::Bounded,
+        }
     }
 
     /// Create map from lines.
@@ -76,7 +204,12 @@ impl<T> Map<T> {
         let w = vecs[0].len();
         let data: Vec<_> = vecs.into_iter().flatten().collect();
         assert_eq!(data.len(), h * w);
-        Map { h, w, data }
+        Map {
+            h,
+            w,
+            data,
+            boundary: Boundary::Bounded,
+        }
     }
 
     // Create new map with same size.
@@ -85,6 +218,35 @@ impl<T> Map<T> {
             h: self.h,
             w: self.w,
             data: vec![val; self.h * self.w],
+            boundary: self.boundary,
+        }
+    }
+
+    /// Turn map into a toroidal map where coordinates wrap around.
+    pub fn toroidal(mut self) -> Self {
+        self.boundary = Boundary::Toroidal;
+        self
+    }
+
+    /// Get boundary mode.
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    /// Set boundary mode.
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Resolve a coordinate according to the boundary mode.
+    ///
+    /// Returns None for coordinates outside of a bounded map, and for any
+    /// coordinate of an empty map.
+    pub fn resolve(&self, p: &Vec2i) -> Option<Vec2i> {
+        match self.boundary {
+            Boundary::Bounded => self.contains(p).then_some(*p),
+            Boundary::Toroidal if self.h == 0 || self.w == 0 => None,
+            Boundary::Toroidal => Some(p.wrap_in_grid(self.h, self.w)),
         }
     }
 
@@ -98,13 +260,52 @@ impl<T> Map<T> {
     }
 
     /// Move in direction within map.
+    ///
+    /// Wraps around for toroidal maps.
     pub fn step_within(&self, pos: &Vec2i, dir: Dir, d: isize) -> Option<Vec2i> {
-        let new = pos.step(dir, d as i64);
-        if self.contains(&new) {
-            Some(new)
-        } else {
-            None
+        self.resolve(&pos.step(dir, d as i64))
+    }
+
+    /// Walk from p in direction dir until the next cell satisfies `stop`.
+    ///
+    /// Returns the last cell before the stop cell (p itself if the first step hits),
+    /// and the stop cell, or None if the ray leaves the map. On toroidal maps the
+    /// ray stops after one full revolution.
+    pub fn cast_ray<F>(&self, p: &Vec2i, dir: Dir, stop: F) -> (Vec2i, Option<Vec2i>)
+    where
+        F: Fn(&T) -> bool,
+    {
+        let mut cur = *p;
+        while let Some(next) = self.step_within(&cur, dir, 1) {
+            if stop(&self[&next]) {
+                return (cur, Some(next));
+            }
+            if next == *p {
+                break;
+            }
+            cur = next;
         }
+        (cur, None)
+    }
+
+    /// Iterate over orthogonal neighbors within map (N, E, S, W order).
+    ///
+    /// Wraps around for toroidal maps.
+    pub fn iter_neighbors4(&self, p: &Vec2i) -> impl Iterator<Item = Vec2i> + '_ {
+        let p = *p;
+        OFFSETS_4
+            .iter()
+            .filter_map(move |d| self.resolve(&(p + *d)))
+    }
+
+    /// Iterate over orthogonal and diagonal neighbors within map (clockwise from N).
+    ///
+    /// Wraps around for toroidal maps.
+    pub fn iter_neighbors8(&self, p: &Vec2i) -> impl Iterator<Item = Vec2i> + '_ {
+        let p = *p;
+        OFFSETS_8
+            .iter()
+            .filter_map(move |d| self.resolve(&(p + *d)))
     }
 
     /// Check if coordinate is within map bounds.
@@ -112,6 +313,38 @@ impl<T> Map<T> {
         p.is_in_grid(self.h, self.w)
     }
 
+    /// Rectangle covering the map.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_size(&Vec2i::new(0, 0), self.h, self.w)
+    }
+
+    /// Coordinate of the only element equal to t.
+    ///
+    /// Returns None if there is no or more than one such element.
+    pub fn find_unique(&self, t: &T) -> Option<Vec2i>
+    where
+        T: PartialEq,
+    {
+        let mut it = self.iter().filter(|(_, v)| *v == t).map(|(p, _)| p);
+        match (it.next(), it.next()) {
+            (Some(p), None) => Some(p),
+            _ => None,
+        }
+    }
+
+    /// Create new map by applying a function to each element.
+    pub fn map_values<S, F>(&self, f: F) -> Map<S>
+    where
+        F: Fn(&T) -> S,
+    {
+        Map {
+            h: self.h,
+            w: self.w,
+            data: self.data.iter().map(f).collect(),
+            boundary: self.boundary,
+        }
+    }
+
     /// Iterate over coordinates.
     pub fn iter_coords(&self) -> impl Iterator<Item = Vec2i> {
         let h = self.h;
@@ -135,32 +368,864 @@ impl<T> Map<T> {
     }
 
     /// Get map element.
+    ///
+    /// Wraps around for toroidal maps.
     pub fn get(&self, p: &Vec2i) -> Option<&T> {
+        let p = self.resolve(p)?;
+        self.data.get(p.linear_idx(self.w))
+    }
+
+    /// Get mutable map element.
+    ///
+    /// Wraps around for toroidal maps.
+    pub fn get_mut(&mut self, p: &Vec2i) -> Option<&mut T> {
+        let p = self.resolve(p)?;
+        self.data.get_mut(p.linear_idx(self.w))
+    }
+}
+
+/// Geometric transforms.
+///
+/// Transforms that only rearrange cells consume the map and permute
+/// its storage in place, so they work for any T.
+impl<T> Map<T> {
+    /// Rearrange cells into a map of size (h, w) s.t. new[i] = old[src(i)].
+    ///
+    /// `src` must be a permutation of 0..h*w.
+    fn permute<F>(mut self, h: usize, w: usize, src: F) -> Self
+    where
+        F: Fn(usize) -> usize,
+    {
+        let mut done = bitvec![0; self.data.len()];
+        for i in 0..self.data.len() {
+            if done[i] {
+                continue;
+            }
+            // follow cycle starting at i
+            let mut j = i;
+            while src(j) != i {
+                self.data.swap(j, src(j));
+                done.set(j, true);
+                j = src(j);
+            }
+            done.set(j, true);
+        }
+        self.h = h;
+        self.w = w;
+        self
+    }
+
+    /// Swap x and y axes.
+    pub fn transpose(self) -> Self {
+        let (h, w) = (self.h, self.w);
+        self.permute(w, h, |i| (i % h) * w + i / h)
+    }
+
+    /// Rotate 90 degrees clockwise.
+    pub fn rotate_cw(self) -> Self {
+        let (h, w) = (self.h, self.w);
+        // new (x, y) comes from old (y, h - 1 - x)
+        self.permute(w, h, |i| (h - 1 - i % h) * w + i / h)
+    }
+
+    /// Rotate 90 degrees counter-clockwise.
+    pub fn rotate_ccw(self) -> Self {
+        let (h, w) = (self.h, self.w);
+        // new (x, y) comes from old (w - 1 - y, x)
+        self.permute(w, h, |i| (i % h) * w + (w - 1 - i / h))
+    }
+
+    /// Rotate 180 degrees.
+    pub fn rotate_180(mut self) -> Self {
+        self.data.reverse();
+        self
+    }
+
+    /// Mirror left-right.
+    pub fn flip_h(mut self) -> Self {
+        for row in self.data.chunks_mut(self.w.max(1)) {
+            row.reverse();
+        }
+        self
+    }
+
+    /// Mirror top-bottom.
+    pub fn flip_v(self) -> Self {
+        let (h, w) = (self.h, self.w);
+        self.permute(h, w, |i| (h - 1 - i / w) * w + i % w)
+    }
+
+    /// Keep the h x w sub-map with top-left corner at `origin`.
+    ///
+    /// The region is clipped to the map.
+    pub fn crop(self, origin: &Vec2i, h: usize, w: usize) -> Self {
+        self.crop_rect(&Rect::from_size(origin, h, w))
+    }
+
+    /// Keep the part of the map inside rect.
+    ///
+    /// The region is clipped to the map.
+    pub fn crop_rect(self, rect: &Rect) -> Self {
+        let r = rect
+            .intersect(&self.bounds())
+            .unwrap_or(Rect::new(Vec2i::new(0, 0), Vec2i::new(0, 0)));
+        let old_w = self.w;
+        let data = self
+            .data
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| r.contains(&Vec2i::new((i % old_w) as i64, (i / old_w) as i64)))
+            .map(|(_, t)| t)
+            .collect();
+        Map {
+            h: r.height(),
+            w: r.width(),
+            data,
+            boundary: self.boundary,
+        }
+    }
+
+    /// Surround map with `border` cells of `fill` on each side.
+    pub fn pad(self, border: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        let (h, w) = (self.h + 2 * border, self.w + 2 * border);
+        let mut data = Vec::with_capacity(h * w);
+        data.resize(border * w, fill.clone());
+        let mut it = self.data.into_iter();
+        for _ in 0..self.h {
+            data.resize(data.len() + border, fill.clone());
+            data.extend(it.by_ref().take(self.w));
+            data.resize(data.len() + border, fill.clone());
+        }
+        data.resize(h * w, fill);
+        Map {
+            h,
+            w,
+            data,
+            boundary: self.boundary,
+        }
+    }
+
+    /// Repeat map nx times horizontally and ny times vertically.
+    pub fn tile(&self, nx: usize, ny: usize) -> Self
+    where
+        T: Clone,
+    {
+        let (h, w) = (self.h * ny, self.w * nx);
+        let mut data = Vec::with_capacity(h * w);
+        for _ in 0..ny {
+            for row in self.rows() {
+                for _ in 0..nx {
+                    data.extend_from_slice(row);
+                }
+            }
+        }
+        Map {
+            h,
+            w,
+            data,
+            boundary: self.boundary,
+        }
+    }
+
+    /// Iterate over rows as slices.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.h).map(|y| &self[y])
+    }
+
+    /// Iterate over column x from top to bottom.
+    pub fn col(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.w);
+        self.data.iter().skip(x).step_by(self.w)
+    }
+}
+
+/// One of the 8 symmetries of a rectangle: optional left-right
+/// mirroring followed by a number of clockwise quarter turns.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct Orientation {
+    pub flipped: bool,
+    pub rotation: u8,
+}
+
+impl Orientation {
+    pub const IDENTITY: Orientation = Orientation {
+        flipped: false,
+        rotation: 0,
+    };
+
+    /// The four rotations.
+    pub const ROTATIONS: [Orientation; 4] = [
+        Orientation::new(false, 0),
+        Orientation::new(false, 1),
+        Orientation::new(false, 2),
+        Orientation::new(false, 3),
+    ];
+
+    /// All rotations and reflections.
+    pub const ALL: [Orientation; 8] = [
+        Orientation::new(false, 0),
+        Orientation::new(false, 1),
+        Orientation::new(false, 2),
+        Orientation::new(false, 3),
+        Orientation::new(true, 0),
+        Orientation::new(true, 1),
+        Orientation::new(true, 2),
+        Orientation::new(true, 3),
+    ];
+
+    pub const fn new(flipped: bool, rotation: u8) -> Self {
+        Orientation {
+            flipped,
+            rotation: rotation % 4,
+        }
+    }
+
+    /// Apply orientation to a map.
+    pub fn apply<T>(&self, map: Map<T>) -> Map<T> {
+        let mut ret = if self.flipped { map.flip_h() } else { map };
+        for _ in 0..self.rotation {
+            ret = ret.rotate_cw();
+        }
+        ret
+    }
+
+    /// Where coordinate p of an h x w map ends up after applying orientation.
+    pub fn apply_coord(&self, p: &Vec2i, h: usize, w: usize) -> Vec2i {
+        let (mut h, mut w) = (h as i64, w as i64);
+        let mut p = *p;
+        if self.flipped {
+            p.x = w - 1 - p.x;
+        }
+        for _ in 0..self.rotation {
+            p = Vec2i::new(h - 1 - p.y, p.x);
+            (h, w) = (w, h);
+        }
+        p
+    }
+}
+
+/// Connected regions.
+impl<T: PartialEq> Map<T> {
+    /// Union orthogonally adjacent cells with equal values.
+    ///
+    /// Elements of the returned set are linear indices y * w + x. The boundary
+    /// condition is ignored, so regions don't wrap around.
+    pub fn equal_regions(&self) -> DisjointSet {
+        let mut set = DisjointSet::new(self.h * self.w);
+        for y in 0..self.h {
+            for x in 0..self.w {
+                let i = y * self.w + x;
+                if x + 1 < self.w && self.data[i] == self.data[i + 1] {
+                    set.union(i, i + 1);
+                }
+                if y + 1 < self.h && self.data[i] == self.data[i + self.w] {
+                    set.union(i, i + self.w);
+                }
+            }
+        }
+        set
+    }
+}
+
+/// Pattern search.
+impl<T: PartialEq> Map<T> {
+    /// Bitmask of cells equal to t.
+    fn mask_of(&self, t: &T) -> BitVec {
+        self.data.iter().map(|x| x == t).collect()
+    }
+
+    /// Find all placements of `pattern` where every non-None cell matches.
+    ///
+    /// Returns the top-left corner of each placement.
+    pub fn find_pattern(&self, pattern: &Map<Option<T>>) -> Vec<Vec2i> {
+        let mut masks = Vec::new();
+        self.find_with_masks(pattern, &mut masks)
+    }
+
+    /// Find all placements of `pattern` under the given orientations.
+    ///
+    /// Returns the top-left corner of each placement of the oriented pattern,
+    /// together with the orientation. Orientations that produce an identical
+    /// pattern (i.e. symmetries of the pattern) are only reported once.
+    pub fn find_pattern_oriented(
+        &self,
+        pattern: &Map<Option<T>>,
+        orientations: &[Orientation],
+    ) -> Vec<(Vec2i, Orientation)>
+    where
+        T: Clone,
+    {
+        let mut seen: Vec<Map<Option<T>>> = Vec::new();
+        let mut masks = Vec::new();
+        let mut ret = Vec::new();
+        for o in orientations {
+            let oriented = o.apply(pattern.clone());
+            if seen
+                .iter()
+                .any(|p| p.h == oriented.h && p.data == oriented.data)
+            {
+                continue;
+            }
+            let found = self.find_with_masks(&oriented, &mut masks);
+            ret.extend(found.into_iter().map(|p| (p, *o)));
+            seen.push(oriented);
+        }
+        ret
+    }
+
+    /// Find all occurrences of `word` along the 8 directions.
+    ///
+    /// Returns (start, step) pairs, where the k:th letter is at start + step * k.
+    /// Palindromes are reported once per line, and a one-letter word once per cell
+    /// with a zero step.
+    pub fn find_word(&self, word: &[T]) -> Vec<(Vec2i, Vec2i)>
+    where
+        T: Clone,
+    {
+        let n = word.len();
+        if n == 0 {
+            return Vec::new();
+        }
+        let straight = Map::from_vecs(vec![word.iter().cloned().map(Some).collect()]);
+        let mut diagonal = Map::new_constant(n, n, None);
+        for (i, c) in word.iter().enumerate() {
+            diagonal[(i, i)] = Some(c.clone());
+        }
+
+        // for a single letter both patterns are the same 1x1 pattern
+        let mut patterns = vec![(straight, Vec2i::new(1, 0))];
+        if n > 1 {
+            patterns.push((diagonal, Vec2i::new(1, 1)));
+        }
+
+        let origin = Vec2i::new(0, 0);
+        let mut ret = Vec::new();
+        for (pattern, step) in patterns {
+            let (h, w) = (pattern.h, pattern.w);
+            let last = step * (n as i64 - 1);
+            for (anchor, o) in self.find_pattern_oriented(&pattern, &Orientation::ROTATIONS) {
+                let start = o.apply_coord(&origin, h, w);
+                let end = o.apply_coord(&last, h, w);
+                let mut dir = end - start;
+                if n > 1 {
+                    dir /= n as i64 - 1;
+                }
+                ret.push((anchor + start, dir));
+            }
+        }
+        ret
+    }
+
+    /// Pattern search with a cache of per-value bitmasks.
+    ///
+    /// A placement at linear index a matches iff for every pattern cell (dx, dy, t),
+    /// the mask of t is set at a + dy * w + dx. The search is done by and-ing shifted masks.
+    fn find_with_masks<'a>(
+        &'a self,
+        pattern: &Map<Option<T>>,
+        masks: &mut Vec<(&'a T, BitVec)>,
+    ) -> Vec<Vec2i> {
+        if pattern.h > self.h || pattern.w > self.w {
+            return Vec::new();
+        }
+        let n = self.data.len();
+
+        // valid anchors
+        let mut cand: BitVec = (0..n)
+            .map(|i| i / self.w + pattern.h <= self.h && i % self.w + pattern.w <= self.w)
+            .collect();
+
+        for (p, t) in pattern.iter() {
+            let Some(t) = t else { continue };
+            let idx = match masks.iter().position(|(x, _)| *x == t) {
+                Some(idx) => idx,
+                None => {
+                    // key masks by an element of self to decouple from the pattern
+                    let Some(key) = self.data.iter().find(|x| *x == t) else {
+                        return Vec::new();
+                    };
+                    masks.push((key, self.mask_of(key)));
+                    masks.len() - 1
+                }
+            };
+            let off = p.linear_idx(self.w);
+            cand[..n - off] &= &masks[idx].1[off..];
+            cand[n - off..].fill(false);
+        }
+
+        cand.iter_ones()
+            .map(|i| Vec2i::new((i % self.w) as i64, (i / self.w) as i64))
+            .collect()
+    }
+}
+
+/// Bit-packed boolean 2D map.
+///
+/// Uses the same "image" coordinates as Map<T>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitMap {
+    pub h: usize,
+    pub w: usize,
+    data: BitVec, // row-major
+}
+
+impl BitMap {
+    /// Create map with all bits cleared.
+    pub fn new(h: usize, w: usize) -> Self {
+        BitMap {
+            h,
+            w,
+            data: bitvec![0; h * w],
+        }
+    }
+
+    /// Create bit map with the same size as a map.
+    pub fn same_size_as<T>(map: &Map<T>) -> Self {
+        Self::new(map.h, map.w)
+    }
+
+    /// Create bit map from a map and a predicate.
+    pub fn from_map<T, F>(map: &Map<T>, f: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        BitMap {
+            h: map.h,
+            w: map.w,
+            data: map.data.iter().map(f).collect(),
+        }
+    }
+
+    /// Check if coordinate is within map bounds.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        p.is_in_grid(self.h, self.w)
+    }
+
+    /// Get bit, None if outside of map.
+    pub fn get(&self, p: &Vec2i) -> Option<bool> {
         match self.contains(p) {
-            true => self.data.get(p.linear_idx(self.w)),
+            true => Some(self.data[p.linear_idx(self.w)]),
             false => None,
         }
     }
 
-    /// Get mutable map element.
-    pub fn get_mut(&mut self, p: &Vec2i) -> Option<&mut T> {
+    /// Set bit. Panics if outside of map.
+    pub fn set(&mut self, p: &Vec2i, val: bool) {
+        assert!(self.contains(p));
+        self.data.set(p.linear_idx(self.w), val);
+    }
+
+    /// Set bit, returns true if it was previously cleared.
+    pub fn insert(&mut self, p: &Vec2i) -> bool {
+        assert!(self.contains(p));
+        !self.data.replace(p.linear_idx(self.w), true)
+    }
+
+    /// Clear all bits.
+    pub fn clear(&mut self) {
+        self.data.fill(false);
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.data.count_ones()
+    }
+
+    /// Iterate over coordinates of set bits in row-major order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = Vec2i> + '_ {
+        self.data
+            .iter_ones()
+            .map(|i| Vec2i::new((i % self.w) as i64, (i / self.w) as i64))
+    }
+
+    /// Move all bits by d. Bits that end up outside of the map are dropped.
+    pub fn shifted(&self, d: &Vec2i) -> Self {
+        let mut ret = Self::new(self.h, self.w);
+        let (h, w) = (self.h as i64, self.w as i64);
+        let (x0, x1) = (d.x.clamp(0, w), (w + d.x).clamp(0, w));
+        if x0 >= x1 {
+            return ret;
+        }
+        for y in (d.y.max(0))..(h + d.y).min(h) {
+            let dst = (y * w) as usize;
+            let src = ((y - d.y) * w) as usize;
+            let (sx0, sx1) = ((x0 - d.x) as usize, (x1 - d.x) as usize);
+            ret.data[dst + x0 as usize..dst + x1 as usize]
+                .copy_from_bitslice(&self.data[src + sx0..src + sx1]);
+        }
+        ret
+    }
+
+    /// Convert to a boolean map.
+    pub fn to_map(&self) -> Map<bool> {
+        Map {
+            h: self.h,
+            w: self.w,
+            data: self.data.iter().by_vals().collect(),
+            boundary: Boundary::Bounded,
+        }
+    }
+}
+
+impl BitOrAssign<&BitMap> for BitMap {
+    fn bitor_assign(&mut self, rhs: &BitMap) {
+        assert_eq!((self.h, self.w), (rhs.h, rhs.w));
+        self.data |= &rhs.data;
+    }
+}
+
+impl BitAndAssign<&BitMap> for BitMap {
+    fn bitand_assign(&mut self, rhs: &BitMap) {
+        assert_eq!((self.h, self.w), (rhs.h, rhs.w));
+        self.data &= &rhs.data;
+    }
+}
+
+impl BitOr for &BitMap {
+    type Output = BitMap;
+    fn bitor(self, rhs: &BitMap) -> Self::Output {
+        let mut ret = self.clone();
+        ret |= rhs;
+        ret
+    }
+}
+
+impl BitAnd for &BitMap {
+    type Output = BitMap;
+    fn bitand(self, rhs: &BitMap) -> Self::Output {
+        let mut ret = self.clone();
+        ret &= rhs;
+        ret
+    }
+}
+
+/// Bit-packed map of (position, direction) states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirBitMap {
+    pub h: usize,
+    pub w: usize,
+    data: BitVec, // row-major, four bits per cell
+}
+
+impl DirBitMap {
+    /// Create map with all bits cleared.
+    pub fn new(h: usize, w: usize) -> Self {
+        DirBitMap {
+            h,
+            w,
+            data: bitvec![0; 4 * h * w],
+        }
+    }
+
+    /// Create bit map with the same size as a map.
+    pub fn same_size_as<T>(map: &Map<T>) -> Self {
+        Self::new(map.h, map.w)
+    }
+
+    fn idx(&self, p: &Vec2i, dir: Dir) -> usize {
+        4 * p.linear_idx(self.w) + dir as usize
+    }
+
+    /// Check if coordinate is within map bounds.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        p.is_in_grid(self.h, self.w)
+    }
+
+    /// Get bit, None if outside of map.
+    pub fn get(&self, p: &Vec2i, dir: Dir) -> Option<bool> {
         match self.contains(p) {
-            true => self.data.get_mut(p.linear_idx(self.w)),
+            true => Some(self.data[self.idx(p, dir)]),
             false => None,
         }
     }
+
+    /// Set bit. Panics if outside of map.
+    pub fn set(&mut self, p: &Vec2i, dir: Dir, val: bool) {
+        assert!(self.contains(p));
+        let idx = self.idx(p, dir);
+        self.data.set(idx, val);
+    }
+
+    /// Set bit, returns true if it was previously cleared.
+    pub fn insert(&mut self, p: &Vec2i, dir: Dir) -> bool {
+        assert!(self.contains(p));
+        let idx = self.idx(p, dir);
+        !self.data.replace(idx, true)
+    }
+
+    /// Clear all bits.
+    pub fn clear(&mut self) {
+        self.data.fill(false);
+    }
+
+    /// Number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.data.count_ones()
+    }
+
+    /// Iterate over set (position, direction) states.
+    pub fn iter_ones(&self) -> impl Iterator<Item = (Vec2i, Dir)> + '_ {
+        self.data.iter_ones().map(|i| {
+            let c = i / 4;
+            (
+                Vec2i::new((c % self.w) as i64, (c / self.w) as i64),
+                DIRECTIONS[i % 4],
+            )
+        })
+    }
+
+    /// Positions where any direction is set.
+    pub fn positions(&self) -> BitMap {
+        BitMap {
+            h: self.h,
+            w: self.w,
+            data: self.data.chunks(4).map(|c| c.any()).collect(),
+        }
+    }
+}
+
+/// Sparse and unbounded 2D map type.
+///
+/// Uses the same "image" coordinates as Map<T>, but may contain
+/// arbitrary (also negative) coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseMap<T> {
+    data: FxHashMap<Vec2i, T>,
+}
+
+impl<T> Default for SparseMap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SparseMap<T> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        SparseMap {
+            data: FxHashMap::new(),
+        }
+    }
+
+    /// Create sparse map from the elements of a dense map that satisfy a predicate.
+    ///
+    /// The dense map's (0, 0) is placed at `origin`.
+    pub fn from_map<F>(map: &Map<T>, origin: &Vec2i, keep: F) -> Self
+    where
+        T: Clone,
+        F: Fn(&T) -> bool,
+    {
+        let mut ret = Self::new();
+        for (p, v) in map.iter().filter(|(_, v)| keep(v)) {
+            ret.insert(p + *origin, v.clone());
+        }
+        ret
+    }
+
+    /// Convert to a dense map covering the bounding box.
+    ///
+    /// Returns the coordinate of the dense map's (0, 0) together with the map,
+    /// or None if the sparse map is empty. Missing cells are set to `fill`.
+    pub fn to_map(&self, fill: T) -> Option<(Vec2i, Map<T>)>
+    where
+        T: Clone,
+    {
+        let bounds = self.bounds()?;
+        let mut ret = Map::new_constant(bounds.height(), bounds.width(), fill);
+        for (p, v) in self.data.iter() {
+            ret[&(*p - bounds.min)] = v.clone();
+        }
+        Some((bounds.min, ret))
+    }
+
+    /// Number of stored elements.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Check if map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Check if coordinate holds an element.
+    pub fn contains(&self, p: &Vec2i) -> bool {
+        self.data.contains_key(p)
+    }
+
+    /// Get map element.
+    pub fn get(&self, p: &Vec2i) -> Option<&T> {
+        self.data.get(p)
+    }
+
+    /// Get mutable map element.
+    pub fn get_mut(&mut self, p: &Vec2i) -> Option<&mut T> {
+        self.data.get_mut(p)
+    }
+
+    /// Insert element, returns previous element if present.
+    pub fn insert(&mut self, p: Vec2i, t: T) -> Option<T> {
+        self.data.insert(p, t)
+    }
+
+    /// Remove element.
+    pub fn remove(&mut self, p: &Vec2i) -> Option<T> {
+        self.data.remove(p)
+    }
+
+    /// Iterate over (coord, val) pairs in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec2i, &T)> {
+        self.data.iter().map(|(p, v)| (*p, v))
+    }
+
+    /// Iterate over orthogonal neighbor coordinates (N, E, S, W order).
+    pub fn iter_neighbors4(p: &Vec2i) -> impl Iterator<Item = Vec2i> {
+        let p = *p;
+        OFFSETS_4.iter().map(move |d| p + *d)
+    }
+
+    /// Iterate over orthogonal and diagonal neighbor coordinates (clockwise from N).
+    pub fn iter_neighbors8(p: &Vec2i) -> impl Iterator<Item = Vec2i> {
+        let p = *p;
+        OFFSETS_8.iter().map(move |d| p + *d)
+    }
+
+    /// Bounding box of stored coordinates, None if the map is empty.
+    pub fn bounds(&self) -> Option<Rect> {
+        Rect::from_points(self.data.keys())
+    }
+}
+
+/// Cell that differs between two maps.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CellChange<'a, T> {
+    pub p: Vec2i,
+    pub old: &'a T,
+    pub new: &'a T,
+}
+
+/// Map comparison.
+impl<T: PartialEq> Map<T> {
+    /// Cells that differ from `other`, in row-major order.
+    ///
+    /// Both maps must have the same size.
+    pub fn diff<'a>(&'a self, other: &'a Map<T>) -> Vec<CellChange<'a, T>> {
+        assert_eq!((self.h, self.w), (other.h, other.w), "map sizes differ");
+        self.iter()
+            .zip(other.iter_values())
+            .filter(|((_, old), new)| old != new)
+            .map(|((p, old), new)| CellChange { p, old, new })
+            .collect()
+    }
+
+    /// Render both maps side by side, followed by a column that marks changed cells with '*'.
+    pub fn diff_side_by_side(&self, other: &Map<T>) -> String
+    where
+        T: GridTile,
+    {
+        let h = self.h.max(other.h);
+        let row_str = |map: &Map<T>, y: usize| -> String {
+            match y < map.h {
+                true => map[y].iter().map(T::to_char).collect(),
+                false => " ".repeat(map.w),
+            }
+        };
+        let mut ret = String::new();
+        for y in 0..h {
+            let marks: String = (0..self.w.max(other.w))
+                .map(|x| {
+                    let p = Vec2i::new(x as i64, y as i64);
+                    match (self.get(&p), other.get(&p)) {
+                        (Some(a), Some(b)) if a == b => ' ',
+                        _ => '*',
+                    }
+                })
+                .collect();
+            let line = format!("{} | {} | {}", row_str(self, y), row_str(other, y), marks);
+            ret.push_str(line.trim_end());
+            if y + 1 < h {
+                ret.push('\n');
+            }
+        }
+        ret
+    }
+
+    /// Render `other` with changed cells highlighted using ANSI colors.
+    pub fn diff_highlighted(&self, other: &Map<T>) -> String
+    where
+        T: GridTile,
+    {
+        let mut ret = String::new();
+        for (y, row) in other.rows().enumerate() {
+            for (x, t) in row.iter().enumerate() {
+                let p = Vec2i::new(x as i64, y as i64);
+                match self.get(&p) == Some(t) {
+                    true => ret.push(t.to_char()),
+                    false => ret.push_str(&format!("\x1b[1;31m{}\x1b[0m", t.to_char())),
+                }
+            }
+            if y + 1 < other.h {
+                ret.push('\n');
+            }
+        }
+        ret
+    }
+
+    /// Human-readable description of the differences to `other`, None if equal.
+    pub fn diff_report(&self, other: &Map<T>) -> Option<String>
+    where
+        T: GridTile + fmt::Debug,
+    {
+        let header = if (self.h, self.w) != (other.h, other.w) {
+            format!(
+                "map sizes differ: {}x{} != {}x{}",
+                self.h, self.w, other.h, other.w
+            )
+        } else {
+            let changes = self.diff(other);
+            if changes.is_empty() {
+                return None;
+            }
+            let mut header = format!("{} cells differ", changes.len());
+            for c in changes.iter().take(10) {
+                header.push_str(&format!(
+                    "\n  ({}, {}): {:?} != {:?}",
+                    c.p.x, c.p.y, c.old, c.new
+                ));
+            }
+            if changes.len() > 10 {
+                header.push_str("\n  ...");
+            }
+            header
+        };
+        Some(format!(
+            "{}\nleft | right | changed\n{}",
+            header,
+            self.diff_side_by_side(other)
+        ))
+    }
 }
 
-impl<T> fmt::Display for Map<T>
-where
-    T: fmt::Display + Sized,
-{
+/// Assert that two maps are equal, printing a readable grid diff if not.
+#[macro_export]
+macro_rules! assert_map_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        if let Some(report) = $crate::map2d::Map::diff_report(&$left, &$right) {
+            panic!("assertion `left == right` failed: {}", report);
+        }
+    };
+}
+
+impl<T: GridTile> fmt::Display for Map<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         for row in 0..self.h {
             for col in 0..self.w {
-                write!(f, "{:}", self.data[self.w * row + col])?
+                write!(f, "{}", self.data[self.w * row + col].to_char())?
             }
-            if row < self.h - 1 {
+            if row + 1 < self.h {
                 writeln!(f)?
             }
         }
@@ -168,17 +1233,84 @@ where
     }
 }
 
+/// Parse map from text, ignoring leading and trailing empty lines.
+///
+/// All rows must have the same length.
+impl<T: GridTile> FromStr for Map<T> {
+    type Err = ParseMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut data = Vec::new();
+        let mut h = 0;
+        let mut w = 0;
+        for (i, line) in s.trim_matches(['\n', '\r']).lines().enumerate() {
+            let mut len = 0;
+            for (j, c) in line.chars().enumerate() {
+                let t = T::from_char(c).map_err(|msg| ParseMapError::InvalidTile {
+                    line: i + 1,
+                    col: j + 1,
+                    c,
+                    msg,
+                })?;
+                data.push(t);
+                len += 1;
+            }
+            if i == 0 {
+                w = len;
+            } else if len != w {
+                return Err(ParseMapError::RaggedRow {
+                    line: i + 1,
+                    len,
+                    expected: w,
+                });
+            }
+            h += 1;
+        }
+        Ok(Map {
+            h,
+            w,
+            data,
+            boundary: Boundary::Bounded,
+        })
+    }
+}
+
+/// Convert into a 2D grid with shape [w, h], without copying.
+impl<T> From<Map<T>> for Grid<T, 2> {
+    fn from(map: Map<T>) -> Self {
+        Grid::from_vec([map.w, map.h], map.data)
+    }
+}
+
+/// Convert from a 2D grid with shape [w, h], without copying.
+impl<T> From<Grid<T, 2>> for Map<T> {
+    fn from(grid: Grid<T, 2>) -> Self {
+        let [w, h] = grid.shape;
+        Map {
+            h,
+            w,
+            data: grid.into_vec(),
+            boundary: Boundary::Bounded,
+        }
+    }
+}
+
 /// Index with Vec2i = (x, y).
+///
+/// Ignores the boundary mode: p must lie inside the map, even on toroidal maps.
+/// Use [`Map::get`] for coordinates that may need wrapping.
 impl<T> Index<&Vec2i> for Map<T> {
     type Output = T;
     fn index(&self, p: &Vec2i) -> &Self::Output {
+        debug_assert!(self.contains(p), "{p:?} outside of map");
         &self.data[p.linear_idx(self.w)]
     }
 }
 
-/// Mutable index with Vec2i = (x, y).
+/// Mutable index with Vec2i = (x, y). Ignores the boundary mode like `Index`.
 impl<T> IndexMut<&Vec2i> for Map<T> {
     fn index_mut(&mut self, p: &Vec2i) -> &mut Self::Output {
+        debug_assert!(self.contains(p), "{p:?} outside of map");
         &mut self.data[p.linear_idx(self.w)]
     }
 }
@@ -280,6 +1412,413 @@ mod tests {
         assert_eq!(map.get(&p), None);
     }
 
+    #[test]
+    fn test_map_toroidal() {
+        let data = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
+        let map = Map::from_vecs(data).toroidal();
+        assert_eq!(map.boundary(), Boundary::Toroidal);
+
+        assert_eq!(map.get(&Vec2i { x: -1, y: 0 }), Some(&4));
+        assert_eq!(map.get(&Vec2i { x: 4, y: -1 }), Some(&9));
+        assert_eq!(map.get(&Vec2i { x: 9, y: 7 }), Some(&6));
+
+        let p = Vec2i { x: 0, y: 0 };
+        assert_eq!(map.step_within(&p, Dir::N, 1), Some(Vec2i { x: 0, y: 2 }));
+        assert_eq!(map.step_within(&p, Dir::W, 5), Some(Vec2i { x: 3, y: 0 }));
+
+        let nbrs = map.iter_neighbors4(&p).collect::<Vec<_>>();
+        assert_eq!(
+            nbrs,
+            vec![
+                Vec2i { x: 0, y: 2 },
+                Vec2i { x: 1, y: 0 },
+                Vec2i { x: 0, y: 1 },
+                Vec2i { x: 3, y: 0 }
+            ]
+        );
+        assert_eq!(map.iter_neighbors8(&p).count(), 8);
+
+        let map = map.same_size_with(0);
+        assert_eq!(map.boundary(), Boundary::Toroidal);
+    }
+
+    #[test]
+    fn test_map_toroidal_empty() {
+        let p = Vec2i { x: 1, y: -1 };
+        for (h, w) in [(0, 0), (0, 3), (3, 0)] {
+            let map = Map::<u8>::new(h, w).toroidal();
+            assert_eq!(map.resolve(&p), None);
+            assert_eq!(map.get(&p), None);
+            assert_eq!(map.step_within(&p, Dir::E, 1), None);
+        }
+    }
+
+    #[test]
+    fn test_equal_regions() {
+        let map: Map<char> = "AAAA\nBBCD\nBBCC\nEEEC".parse().unwrap();
+        let mut regions = map.equal_regions();
+        assert_eq!(regions.num_components(), 5);
+        let area = |regions: &mut DisjointSet, x: i64, y: i64| {
+            regions.size_of(Vec2i::new(x, y).linear_idx(map.w))
+        };
+        assert_eq!(area(&mut regions, 0, 0), 4);
+        assert_eq!(area(&mut regions, 2, 1), 4);
+        assert_eq!(area(&mut regions, 3, 1), 1);
+
+        // the two O regions touch only diagonally
+        let map: Map<char> = "OX\nXO".parse().unwrap();
+        assert_eq!(map.equal_regions().num_components(), 4);
+    }
+
+    #[test]
+    fn test_cast_ray() {
+        let map: Map<char> = "..#.\n....\n#...".parse().unwrap();
+        let p = Vec2i { x: 2, y: 2 };
+        assert_eq!(
+            map.cast_ray(&p, Dir::N, |c| *c == '#'),
+            (Vec2i { x: 2, y: 1 }, Some(Vec2i { x: 2, y: 0 }))
+        );
+        assert_eq!(
+            map.cast_ray(&p, Dir::W, |c| *c == '#'),
+            (Vec2i { x: 1, y: 2 }, Some(Vec2i { x: 0, y: 2 }))
+        );
+        assert_eq!(
+            map.cast_ray(&p, Dir::E, |c| *c == '#'),
+            (Vec2i { x: 3, y: 2 }, None)
+        );
+
+        let map = map.toroidal();
+        assert_eq!(
+            map.cast_ray(&p, Dir::E, |c| *c == '#'),
+            (Vec2i { x: 3, y: 2 }, Some(Vec2i { x: 0, y: 2 }))
+        );
+        assert_eq!(
+            map.cast_ray(&Vec2i { x: 0, y: 1 }, Dir::E, |c| *c == '#'),
+            (Vec2i { x: 3, y: 1 }, None)
+        );
+    }
+
+    #[test]
+    fn test_map_neighbors_bounded() {
+        let map = Map::<u8>::new(3, 4);
+        let p = Vec2i { x: 0, y: 0 };
+        assert_eq!(map.step_within(&p, Dir::N, 1), None);
+        assert_eq!(
+            map.iter_neighbors4(&p).collect::<Vec<_>>(),
+            vec![Vec2i { x: 1, y: 0 }, Vec2i { x: 0, y: 1 }]
+        );
+        assert_eq!(map.iter_neighbors8(&p).count(), 3);
+        assert_eq!(map.iter_neighbors8(&Vec2i { x: 1, y: 1 }).count(), 8);
+    }
+
+    #[test]
+    fn test_map_transforms() {
+        let data = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let map = Map::from_vecs(data);
+
+        let t = map.clone().transpose();
+        assert_eq!((t.h, t.w), (3, 2));
+        assert_eq!(t.rows().collect::<Vec<_>>(), vec![[1, 4], [2, 5], [3, 6]]);
+
+        let r = map.clone().rotate_cw();
+        assert_eq!((r.h, r.w), (3, 2));
+        assert_eq!(r.rows().collect::<Vec<_>>(), vec![[4, 1], [5, 2], [6, 3]]);
+
+        let r = map.clone().rotate_ccw();
+        assert_eq!(r.rows().collect::<Vec<_>>(), vec![[3, 6], [2, 5], [1, 4]]);
+
+        let r = map.clone().rotate_cw().rotate_cw();
+        assert_eq!(r.rows().collect::<Vec<_>>(), vec![[6, 5, 4], [3, 2, 1]]);
+        let r2 = map.clone().rotate_180();
+        assert_eq!(r.rows().collect::<Vec<_>>(), r2.rows().collect::<Vec<_>>());
+
+        let f = map.clone().flip_h();
+        assert_eq!(f.rows().collect::<Vec<_>>(), vec![[3, 2, 1], [6, 5, 4]]);
+
+        let f = map.clone().flip_v();
+        assert_eq!(f.rows().collect::<Vec<_>>(), vec![[4, 5, 6], [1, 2, 3]]);
+
+        assert_eq!(map.col(1).collect::<Vec<_>>(), vec![&2, &5]);
+    }
+
+    #[test]
+    fn test_map_transforms_non_clone() {
+        #[derive(Debug, PartialEq)]
+        struct NoClone(u8);
+
+        let map = Map::from_iterators((0..3).map(|y| (0..4).map(move |x| NoClone(4 * y + x))));
+        let map = map.rotate_cw().rotate_cw().rotate_cw().rotate_cw();
+        assert_eq!(map[(2, 3)], NoClone(11));
+        let map = map.transpose().transpose();
+        assert_eq!(map[(1, 2)], NoClone(6));
+    }
+
+    #[test]
+    fn test_map_resize() {
+        let data = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let map = Map::from_vecs(data);
+
+        let c = map.clone().crop(&Vec2i { x: 1, y: 0 }, 5, 5);
+        assert_eq!(c.rows().collect::<Vec<_>>(), vec![[2, 3], [5, 6]]);
+
+        let c = map.clone().crop(&Vec2i { x: -1, y: 1 }, 1, 2);
+        assert_eq!(c.rows().collect::<Vec<_>>(), vec![[4]]);
+
+        let p = map.clone().pad(1, 0);
+        assert_eq!(
+            format!("{}", p.map_values(|x| *x as u32)),
+            "00000\n01230\n04560\n00000"
+        );
+
+        let t = map.tile(2, 2);
+        assert_eq!((t.h, t.w), (4, 6));
+        assert_eq!(t[3], [4, 5, 6, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_orientation() {
+        let data = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let map = Map::from_vecs(data);
+        for o in Orientation::ALL {
+            let oriented = o.apply(map.clone());
+            for (p, v) in map.iter() {
+                assert_eq!(oriented[&o.apply_coord(&p, map.h, map.w)], *v);
+            }
+        }
+    }
+
+    #[test]
+    fn test_find_pattern() {
+        let map = Map::from_lines("abab\nbaba\nabab".lines(), &|c| c);
+        let pattern = Map::from_lines("a.\n.a".lines(), &|c| (c != '.').then_some(c));
+        assert_eq!(
+            map.find_pattern(&pattern),
+            vec![
+                Vec2i { x: 0, y: 0 },
+                Vec2i { x: 2, y: 0 },
+                Vec2i { x: 1, y: 1 }
+            ]
+        );
+
+        // symmetric pattern is only reported for distinct orientations
+        let found = map.find_pattern_oriented(&pattern, &Orientation::ALL);
+        assert_eq!(found.len(), 6);
+
+        let pattern = Map::from_lines("aaaaa".lines(), &|c| Some(c));
+        assert!(map.find_pattern(&pattern).is_empty());
+    }
+
+    #[test]
+    fn test_find_word() {
+        let map = Map::from_lines("xmas\nmm..\na.a.\ns..s".lines(), &|c| c);
+        let mut found = map.find_word(&['x', 'm', 'a', 's']);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                (Vec2i { x: 0, y: 0 }, Vec2i { x: 0, y: 1 }),
+                (Vec2i { x: 0, y: 0 }, Vec2i { x: 1, y: 0 }),
+                (Vec2i { x: 0, y: 0 }, Vec2i { x: 1, y: 1 }),
+            ]
+        );
+
+        let map = Map::from_lines("aba".lines(), &|c| c);
+        assert_eq!(map.find_word(&['a', 'b', 'a']).len(), 1);
+
+        let map = Map::from_lines("ab\nba".lines(), &|c| c);
+        let mut found = map.find_word(&['a']);
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                (Vec2i { x: 0, y: 0 }, Vec2i { x: 0, y: 0 }),
+                (Vec2i { x: 1, y: 1 }, Vec2i { x: 0, y: 0 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bitmap() {
+        let mut bits = BitMap::new(3, 4);
+        assert_eq!(bits.count_ones(), 0);
+        assert!(bits.insert(&Vec2i { x: 1, y: 0 }));
+        assert!(!bits.insert(&Vec2i { x: 1, y: 0 }));
+        bits.set(&Vec2i { x: 3, y: 2 }, true);
+        assert_eq!(bits.get(&Vec2i { x: 3, y: 2 }), Some(true));
+        assert_eq!(bits.get(&Vec2i { x: 2, y: 2 }), Some(false));
+        assert_eq!(bits.get(&Vec2i { x: 4, y: 2 }), None);
+        assert_eq!(
+            bits.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i { x: 1, y: 0 }, Vec2i { x: 3, y: 2 }]
+        );
+
+        let shifted = bits.shifted(&Vec2i { x: -1, y: 1 });
+        assert_eq!(
+            shifted.iter_ones().collect::<Vec<_>>(),
+            vec![Vec2i { x: 0, y: 1 }]
+        );
+        let shifted = bits.shifted(&Vec2i { x: 1, y: -2 });
+        assert_eq!(shifted.count_ones(), 0);
+
+        let union = &bits | &shifted;
+        assert_eq!(union, bits);
+        let inter = &bits & &bits.shifted(&Vec2i { x: 0, y: 0 });
+        assert_eq!(inter, bits);
+        let mut inter = bits.clone();
+        inter &= &BitMap::new(3, 4);
+        assert_eq!(inter.count_ones(), 0);
+
+        let map = bits.to_map();
+        assert_eq!(BitMap::from_map(&map, |b| *b), bits);
+    }
+
+    #[test]
+    fn test_dir_bitmap() {
+        let mut bits = DirBitMap::new(2, 2);
+        assert!(bits.insert(&Vec2i { x: 1, y: 1 }, Dir::W));
+        assert!(bits.insert(&Vec2i { x: 1, y: 1 }, Dir::N));
+        assert!(!bits.insert(&Vec2i { x: 1, y: 1 }, Dir::N));
+        assert_eq!(bits.get(&Vec2i { x: 1, y: 1 }, Dir::E), Some(false));
+        assert_eq!(bits.count_ones(), 2);
+        assert_eq!(
+            bits.iter_ones().collect::<Vec<_>>(),
+            vec![
+                (Vec2i { x: 1, y: 1 }, Dir::N),
+                (Vec2i { x: 1, y: 1 }, Dir::W)
+            ]
+        );
+        assert_eq!(bits.positions().count_ones(), 1);
+    }
+
+    #[test]
+    fn test_map_from_str() {
+        let map = "\n#..\n.#.\n".parse::<Map<bool>>().unwrap();
+        assert_eq!((map.h, map.w), (2, 3));
+        assert!(map[(1, 1)]);
+        assert_eq!(format!("{}", map), "#..\n.#.");
+
+        let map = "123\n456".parse::<Map<u32>>().unwrap();
+        assert_eq!(map[(1, 2)], 6);
+        assert_eq!(format!("{}", map), "123\n456");
+
+        let map = "".parse::<Map<char>>().unwrap();
+        assert_eq!((map.h, map.w), (0, 0));
+        assert_eq!(format!("{}", map), "");
+    }
+
+    #[test]
+    fn test_map_from_str_errors() {
+        let err = "#..\n.x.".parse::<Map<bool>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseMapError::InvalidTile {
+                line: 2,
+                col: 2,
+                c: 'x',
+                msg: "Invalid bool x".into()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid tile 'x' at line 2, column 2: Invalid bool x"
+        );
+
+        let err = "#..\n.#".parse::<Map<bool>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseMapError::RaggedRow {
+                line: 2,
+                len: 2,
+                expected: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_map_diff() {
+        let a: Map<char> = "#..\n.#.".parse().unwrap();
+        let b: Map<char> = "#.x\n...".parse().unwrap();
+        assert!(a.diff(&a).is_empty());
+        assert_eq!(
+            a.diff(&b),
+            vec![
+                CellChange {
+                    p: Vec2i { x: 2, y: 0 },
+                    old: &'.',
+                    new: &'x'
+                },
+                CellChange {
+                    p: Vec2i { x: 1, y: 1 },
+                    old: &'#',
+                    new: &'.'
+                }
+            ]
+        );
+        assert_eq!(a.diff_side_by_side(&b), "#.. | #.x |   *\n.#. | ... |  *");
+        assert_eq!(
+            a.diff_highlighted(&b),
+            "#.\x1b[1;31mx\x1b[0m\n.\x1b[1;31m.\x1b[0m."
+        );
+        assert_eq!(a.diff_report(&a), None);
+        assert!(a.diff_report(&b).unwrap().starts_with("2 cells differ"));
+
+        let c: Map<char> = "#..".parse().unwrap();
+        assert!(a
+            .diff_report(&c)
+            .unwrap()
+            .starts_with("map sizes differ: 2x3 != 1x3"));
+    }
+
+    #[test]
+    fn test_assert_map_eq() {
+        let a: Map<char> = "#..\n.#.".parse().unwrap();
+        crate::assert_map_eq!(a, a.clone());
+    }
+
+    #[test]
+    #[should_panic(expected = "1 cells differ")]
+    fn test_assert_map_eq_fails() {
+        let a: Map<char> = "#..\n.#.".parse().unwrap();
+        let b: Map<char> = "#..\n.##".parse().unwrap();
+        crate::assert_map_eq!(a, b);
+    }
+
+    #[test]
+    fn test_find_unique() {
+        let map = "S..\n.#E\n.#.".parse::<Map<char>>().unwrap();
+        assert_eq!(map.find_unique(&'S'), Some(Vec2i { x: 0, y: 0 }));
+        assert_eq!(map.find_unique(&'E'), Some(Vec2i { x: 2, y: 1 }));
+        assert_eq!(map.find_unique(&'#'), None);
+        assert_eq!(map.find_unique(&'X'), None);
+    }
+
+    #[test]
+    fn test_sparse_map() {
+        let mut sparse = SparseMap::new();
+        assert!(sparse.is_empty());
+        assert_eq!(sparse.bounds(), None);
+        assert!(sparse.to_map('.').is_none());
+
+        sparse.insert(Vec2i { x: -2, y: 1 }, '#');
+        sparse.insert(Vec2i { x: 1, y: -1 }, '#');
+        assert_eq!(sparse.len(), 2);
+        assert!(sparse.contains(&Vec2i { x: -2, y: 1 }));
+        assert_eq!(
+            sparse.bounds(),
+            Some(Rect::new(Vec2i { x: -2, y: -1 }, Vec2i { x: 2, y: 2 }))
+        );
+
+        let (origin, dense) = sparse.to_map('.').unwrap();
+        assert_eq!(origin, Vec2i { x: -2, y: -1 });
+        assert_eq!(format!("{}", dense), "...#\n....\n#...");
+
+        let back = SparseMap::from_map(&dense, &origin, |c| *c == '#');
+        assert_eq!(back, sparse);
+
+        assert_eq!(sparse.remove(&Vec2i { x: 1, y: -1 }), Some('#'));
+        assert_eq!(sparse.len(), 1);
+    }
+
     #[test]
     fn test_map_iter() {
         let data = vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10, 11, 12]];
@@ -14,6 +14,14 @@ pub fn gcd(mut n: u64, mut m: u64) -> u64 {
     n
 }
 
+/// Greatest common divisor of two 128-bit numbers, with gcd(n, 0) = n.
+pub fn gcd_u128(mut n: u128, mut m: u128) -> u128 {
+    while m != 0 {
+        (n, m) = (m, n % m);
+    }
+    n
+}
+
 /// Least common multiple of two numbers.
 pub fn lcm(n: u64, m: u64) -> u64 {
     n * (m / gcd(n, m))
@@ -251,6 +259,14 @@ mod tests {
         assert_eq!(gcd(48, 56), 8);
     }
 
+    #[test]
+    fn test_gcd_u128() {
+        assert_eq!(gcd_u128(48, 56), 8);
+        assert_eq!(gcd_u128(0, 7), 7);
+        assert_eq!(gcd_u128(7, 0), 7);
+        assert_eq!(gcd_u128(3 << 100, 6 << 90), 6 << 90);
+    }
+
     #[test]
     fn test_lcm() {
         assert_eq!(lcm(1, 1), 1);
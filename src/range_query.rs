@@ -0,0 +1,512 @@
+use crate::vec2::Scalar;
+use std::ops::Range;
+
+/// Fenwick tree (binary indexed tree) for prefix sums with point updates.
+///
+/// https://en.wikipedia.org/wiki/Fenwick_tree
+#[derive(Debug, Clone)]
+pub struct FenwickTree<T> {
+    data: Vec<T>, // data[i - 1] holds the sum of (i - lowbit(i), i]
+}
+
+impl<T: Scalar> FenwickTree<T> {
+    /// Create tree of n zeros.
+    pub fn new(n: usize) -> Self {
+        FenwickTree {
+            data: vec![T::ZERO; n],
+        }
+    }
+
+    /// Create tree from values in O(n).
+    pub fn from_slice(values: &[T]) -> Self {
+        let mut data = values.to_vec();
+        for i in 1..=data.len() {
+            let parent = i + (i & i.wrapping_neg());
+            if parent <= data.len() {
+                let v = data[i - 1];
+                data[parent - 1] += v;
+            }
+        }
+        FenwickTree { data }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Add delta to element i.
+    pub fn add(&mut self, i: usize, delta: T) {
+        assert!(i < self.len());
+        let mut i = i + 1;
+        while i <= self.len() {
+            self.data[i - 1] += delta;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    /// Sum of the first n elements.
+    pub fn prefix_sum(&self, n: usize) -> T {
+        assert!(n <= self.len());
+        let mut ret = T::ZERO;
+        let mut i = n;
+        while i > 0 {
+            ret += self.data[i - 1];
+            i -= i & i.wrapping_neg();
+        }
+        ret
+    }
+
+    /// Sum of elements in range.
+    pub fn range_sum(&self, range: Range<usize>) -> T {
+        if range.is_empty() {
+            return T::ZERO;
+        }
+        self.prefix_sum(range.end) - self.prefix_sum(range.start)
+    }
+}
+
+/// Values and lazy updates for a SegmentTree.
+///
+/// Values form a monoid under combine. Updates act on the combined value of a
+/// whole segment, which is why apply gets the number of elements in it.
+pub trait Monoid {
+    type Value: Clone;
+    type Update: Clone;
+
+    fn identity() -> Self::Value;
+    fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value;
+    /// Apply update to the combined value of len elements.
+    fn apply(u: &Self::Update, v: &Self::Value, len: usize) -> Self::Value;
+    /// Update that has the effect of first applying old, then new.
+    fn compose(new: &Self::Update, old: &Self::Update) -> Self::Update;
+}
+
+/// Sums, with range addition.
+pub struct Sum;
+
+impl Monoid for Sum {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        0
+    }
+    fn combine(a: &i64, b: &i64) -> i64 {
+        a + b
+    }
+    fn apply(u: &i64, v: &i64, len: usize) -> i64 {
+        v + u * len as i64
+    }
+    fn compose(new: &i64, old: &i64) -> i64 {
+        new + old
+    }
+}
+
+/// Minimum, with range addition.
+pub struct Min;
+
+impl Monoid for Min {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        i64::MAX
+    }
+    fn combine(a: &i64, b: &i64) -> i64 {
+        *a.min(b)
+    }
+    fn apply(u: &i64, v: &i64, _: usize) -> i64 {
+        v.saturating_add(*u)
+    }
+    fn compose(new: &i64, old: &i64) -> i64 {
+        new + old
+    }
+}
+
+/// Maximum, with range addition.
+pub struct Max;
+
+impl Monoid for Max {
+    type Value = i64;
+    type Update = i64;
+
+    fn identity() -> i64 {
+        i64::MIN
+    }
+    fn combine(a: &i64, b: &i64) -> i64 {
+        *a.max(b)
+    }
+    fn apply(u: &i64, v: &i64, _: usize) -> i64 {
+        v.saturating_add(*u)
+    }
+    fn compose(new: &i64, old: &i64) -> i64 {
+        new + old
+    }
+}
+
+/// Runs of free cells, with range assignment of free (true) or used (false).
+pub struct FreeRuns;
+
+/// Summary of a segment for FreeRuns.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct FreeRun {
+    pub len: usize,
+    /// Free cells at the start of the segment.
+    pub prefix: usize,
+    /// Free cells at the end of the segment.
+    pub suffix: usize,
+    /// Longest run of free cells.
+    pub longest: usize,
+}
+
+impl FreeRun {
+    /// Single free or used cell.
+    pub fn cell(free: bool) -> Self {
+        let n = free as usize;
+        FreeRun {
+            len: 1,
+            prefix: n,
+            suffix: n,
+            longest: n,
+        }
+    }
+}
+
+impl Monoid for FreeRuns {
+    type Value = FreeRun;
+    type Update = bool;
+
+    fn identity() -> FreeRun {
+        FreeRun::default()
+    }
+    fn combine(a: &FreeRun, b: &FreeRun) -> FreeRun {
+        FreeRun {
+            len: a.len + b.len,
+            prefix: if a.prefix == a.len {
+                a.len + b.prefix
+            } else {
+                a.prefix
+            },
+            suffix: if b.suffix == b.len {
+                b.len + a.suffix
+            } else {
+                b.suffix
+            },
+            longest: a.longest.max(b.longest).max(a.suffix + b.prefix),
+        }
+    }
+    fn apply(free: &bool, v: &FreeRun, _: usize) -> FreeRun {
+        let n = if *free { v.len } else { 0 };
+        FreeRun {
+            len: v.len,
+            prefix: n,
+            suffix: n,
+            longest: n,
+        }
+    }
+    fn compose(new: &bool, _: &bool) -> bool {
+        *new
+    }
+}
+
+/// Segment tree with lazy range updates.
+///
+/// https://en.wikipedia.org/wiki/Segment_tree
+pub struct SegmentTree<M: Monoid> {
+    n: usize,
+    tree: Vec<M::Value>, // node 1 is the root, children of i are 2i and 2i + 1
+    lazy: Vec<Option<M::Update>>, // pending update for the children of a node
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    /// Create tree of n identity values.
+    pub fn new(n: usize) -> Self {
+        Self::from_vec(vec![M::identity(); n])
+    }
+
+    /// Create tree from values in O(n).
+    pub fn from_vec(values: Vec<M::Value>) -> Self {
+        let n = values.len();
+        let mut ret = SegmentTree {
+            n,
+            tree: vec![M::identity(); 4 * n.max(1)],
+            lazy: vec![None; 4 * n.max(1)],
+        };
+        if n > 0 {
+            ret.build(1, 0, n, &values);
+        }
+        ret
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Combined value of elements in range.
+    pub fn query(&mut self, range: Range<usize>) -> M::Value {
+        assert!(range.end <= self.n);
+        if range.is_empty() {
+            return M::identity();
+        }
+        self.query_rec(1, 0, self.n, &range)
+    }
+
+    /// Value of element i.
+    pub fn get(&mut self, i: usize) -> M::Value {
+        self.query(i..i + 1)
+    }
+
+    /// Apply update to every element in range.
+    pub fn update(&mut self, range: Range<usize>, u: &M::Update) {
+        assert!(range.end <= self.n);
+        if !range.is_empty() {
+            self.update_rec(1, 0, self.n, &range, u);
+        }
+    }
+
+    /// Replace element i.
+    pub fn set(&mut self, i: usize, v: M::Value) {
+        assert!(i < self.n);
+        self.set_rec(1, 0, self.n, i, v);
+    }
+
+    /// Smallest i >= lo such that pred holds for the combined value of lo..=i.
+    ///
+    /// pred must be monotone: once it holds for lo..=i it holds for all larger i.
+    pub fn find_first<F>(&mut self, lo: usize, pred: F) -> Option<usize>
+    where
+        F: Fn(&M::Value) -> bool,
+    {
+        if lo >= self.n {
+            return None;
+        }
+        let mut acc = M::identity();
+        self.find_first_rec(1, 0, self.n, lo, &mut acc, &pred)
+    }
+
+    fn build(&mut self, node: usize, l: usize, r: usize, values: &[M::Value]) {
+        if r - l == 1 {
+            self.tree[node] = values[l].clone();
+            return;
+        }
+        let m = (l + r) / 2;
+        self.build(2 * node, l, m, values);
+        self.build(2 * node + 1, m, r, values);
+        self.pull(node);
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    /// Apply update to a node covering len elements.
+    fn apply_node(&mut self, node: usize, len: usize, u: &M::Update) {
+        self.tree[node] = M::apply(u, &self.tree[node], len);
+        if len > 1 {
+            self.lazy[node] = Some(match &self.lazy[node] {
+                Some(old) => M::compose(u, old),
+                None => u.clone(),
+            });
+        }
+    }
+
+    /// Pass pending update on to the children.
+    fn push(&mut self, node: usize, l: usize, r: usize) {
+        if let Some(u) = self.lazy[node].take() {
+            let m = (l + r) / 2;
+            self.apply_node(2 * node, m - l, &u);
+            self.apply_node(2 * node + 1, r - m, &u);
+        }
+    }
+
+    fn query_rec(&mut self, node: usize, l: usize, r: usize, range: &Range<usize>) -> M::Value {
+        if range.end <= l || r <= range.start {
+            return M::identity();
+        }
+        if range.start <= l && r <= range.end {
+            return self.tree[node].clone();
+        }
+        self.push(node, l, r);
+        let m = (l + r) / 2;
+        let a = self.query_rec(2 * node, l, m, range);
+        let b = self.query_rec(2 * node + 1, m, r, range);
+        M::combine(&a, &b)
+    }
+
+    fn update_rec(&mut self, node: usize, l: usize, r: usize, range: &Range<usize>, u: &M::Update) {
+        if range.end <= l || r <= range.start {
+            return;
+        }
+        if range.start <= l && r <= range.end {
+            self.apply_node(node, r - l, u);
+            return;
+        }
+        self.push(node, l, r);
+        let m = (l + r) / 2;
+        self.update_rec(2 * node, l, m, range, u);
+        self.update_rec(2 * node + 1, m, r, range, u);
+        self.pull(node);
+    }
+
+    fn set_rec(&mut self, node: usize, l: usize, r: usize, i: usize, v: M::Value) {
+        if r - l == 1 {
+            self.tree[node] = v;
+            return;
+        }
+        self.push(node, l, r);
+        let m = (l + r) / 2;
+        if i < m {
+            self.set_rec(2 * node, l, m, i, v);
+        } else {
+            self.set_rec(2 * node + 1, m, r, i, v);
+        }
+        self.pull(node);
+    }
+
+    fn find_first_rec<F>(
+        &mut self,
+        node: usize,
+        l: usize,
+        r: usize,
+        lo: usize,
+        acc: &mut M::Value,
+        pred: &F,
+    ) -> Option<usize>
+    where
+        F: Fn(&M::Value) -> bool,
+    {
+        if r <= lo {
+            return None;
+        }
+        if lo <= l {
+            // node is completely inside the search range, skip it if pred still fails
+            let combined = M::combine(acc, &self.tree[node]);
+            if !pred(&combined) {
+                *acc = combined;
+                return None;
+            }
+            if r - l == 1 {
+                return Some(l);
+            }
+        }
+        self.push(node, l, r);
+        let m = (l + r) / 2;
+        self.find_first_rec(2 * node, l, m, lo, acc, pred)
+            .or_else(|| self.find_first_rec(2 * node + 1, m, r, lo, acc, pred))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    #[test]
+    fn test_fenwick() {
+        let values = [3i64, -1, 4, 1, -5, 9, 2, 6];
+        let mut tree = FenwickTree::from_slice(&values);
+        let mut naive = values.to_vec();
+        for (i, d) in [(0, 2), (5, -3), (7, 1), (3, 10)] {
+            tree.add(i, d);
+            naive[i] += d;
+            for a in 0..=naive.len() {
+                for b in a..=naive.len() {
+                    assert_eq!(tree.range_sum(a..b), naive[a..b].iter().sum::<i64>());
+                }
+            }
+        }
+        assert_eq!(FenwickTree::<u32>::new(5).prefix_sum(5), 0);
+    }
+
+    #[test]
+    fn test_segment_tree_random() {
+        let n = 37;
+        let mut rng = Lcg::new(3);
+        let mut naive: Vec<i64> = (0..n).map(|_| rng.below(100) as i64).collect();
+        let mut sum = SegmentTree::<Sum>::from_vec(naive.clone());
+        let mut min = SegmentTree::<Min>::from_vec(naive.clone());
+        let mut max = SegmentTree::<Max>::from_vec(naive.clone());
+        for _ in 0..300 {
+            let (a, b) = (rng.below(n), rng.below(n));
+            let range = a.min(b)..a.max(b) + 1;
+            match rng.below(3) {
+                0 => {
+                    let d = rng.below(21) as i64 - 10;
+                    sum.update(range.clone(), &d);
+                    min.update(range.clone(), &d);
+                    max.update(range.clone(), &d);
+                    naive[range].iter_mut().for_each(|x| *x += d);
+                }
+                1 => {
+                    let v = rng.below(100) as i64;
+                    sum.set(a, v);
+                    min.set(a, v);
+                    max.set(a, v);
+                    naive[a] = v;
+                }
+                _ => {
+                    let s = &naive[range.clone()];
+                    assert_eq!(sum.query(range.clone()), s.iter().sum::<i64>());
+                    assert_eq!(min.query(range.clone()), *s.iter().min().unwrap());
+                    assert_eq!(max.query(range.clone()), *s.iter().max().unwrap());
+                }
+            }
+            let expected = (a..n).find(|i| naive[*i] > 80);
+            assert_eq!(max.find_first(a, |m| *m > 80), expected);
+            let expected = (a..n).find(|i| naive[*i] < 20);
+            assert_eq!(min.find_first(a, |m| *m < 20), expected);
+        }
+        assert_eq!(sum.get(4), naive[4]);
+
+        // with non-negative values the running sum is monotone
+        let values: Vec<i64> = (0..n).map(|_| rng.below(20) as i64).collect();
+        let mut sum = SegmentTree::<Sum>::from_vec(values.clone());
+        for lo in 0..n {
+            let expected = (lo..n).find(|i| values[lo..=*i].iter().sum::<i64>() > 100);
+            assert_eq!(sum.find_first(lo, |s| *s > 100), expected);
+        }
+    }
+
+    #[test]
+    fn test_free_runs() {
+        // day 9: move files into the leftmost free span that fits, starting from the right
+        let disk = "2333133121414131402";
+        let mut cells = vec![];
+        let mut files = vec![];
+        for (i, c) in disk.chars().enumerate() {
+            let len = c.to_digit(10).unwrap() as usize;
+            if i % 2 == 0 {
+                files.push((cells.len(), len));
+            }
+            cells.extend((0..len).map(|_| FreeRun::cell(i % 2 == 1)));
+        }
+        let mut tree = SegmentTree::<FreeRuns>::from_vec(cells);
+        let mut checksum = 0;
+        for (id, (pos, len)) in files.into_iter().enumerate().rev() {
+            // the first free run of length len ends where the longest run first reaches len
+            let end = tree
+                .find_first(0, |v| v.longest >= len)
+                .filter(|end| *end < pos);
+            let new_pos = match end {
+                Some(end) => {
+                    let new_pos = end + 1 - len;
+                    tree.update(pos..pos + len, &true);
+                    tree.update(new_pos..new_pos + len, &false);
+                    new_pos
+                }
+                None => pos,
+            };
+            checksum += id * (new_pos * len + len * (len - 1) / 2);
+        }
+        assert_eq!(checksum, 2858);
+        assert_eq!(tree.query(0..tree.len()).len, 42);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::image::Canvas;
+use crate::map2d::{GridTile, Map};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+
+#[cfg(feature = "visualize")]
+mod global {
+    use super::Recording;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    static ENABLED: AtomicBool = AtomicBool::new(false);
+    static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    pub fn start() {
+        *RECORDING.lock().unwrap() = Some(Recording::new());
+        ENABLED.store(true, Ordering::Relaxed);
+    }
+
+    pub fn finish() -> Option<Recording> {
+        ENABLED.store(false, Ordering::Relaxed);
+        RECORDING.lock().unwrap().take()
+    }
+
+    pub fn push(frame: super::Map<char>) {
+        if let Some(recording) = RECORDING.lock().unwrap().as_mut() {
+            recording.push(frame);
+        }
+    }
+}
+
+/// Check if frames are currently being recorded.
+#[inline(always)]
+pub fn is_enabled() -> bool {
+    #[cfg(feature = "visualize")]
+    return global::is_enabled();
+    #[cfg(not(feature = "visualize"))]
+    false
+}
+
+/// Start a new global recording, discarding any previous one.
+pub fn start() {
+    #[cfg(feature = "visualize")]
+    global::start();
+}
+
+/// Stop recording and return the recorded frames.
+///
+/// Returns None if no recording was started.
+pub fn finish() -> Option<Recording> {
+    #[cfg(feature = "visualize")]
+    return global::finish();
+    #[cfg(not(feature = "visualize"))]
+    None
+}
+
+/// Record map as a frame.
+///
+/// Only records when built with the `visualize` feature and a recording
+/// has been started, otherwise this compiles to nothing.
+#[inline(always)]
+pub fn record<T: GridTile>(map: &Map<T>) {
+    if is_enabled() {
+        #[cfg(feature = "visualize")]
+        global::push(map.map_values(T::to_char));
+    }
+    #[cfg(not(feature = "visualize"))]
+    let _ = map;
+}
+
+/// Record the map returned by f as a frame.
+///
+/// f is only evaluated while recording.
+#[inline(always)]
+pub fn record_with<T, F>(f: F)
+where
+    T: GridTile,
+    F: FnOnce() -> Map<T>,
+{
+    if is_enabled() {
+        record(&f());
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Frame {
+    Key(Map<char>),
+    Diff(Vec<(usize, char)>),
+}
+
+/// Sequence of character map frames.
+///
+/// Frames are stored as differences to the previous frame, except when
+/// the map size changes.
+#[derive(Debug, Clone, Default)]
+pub struct Recording {
+    frames: Vec<Frame>,
+    last: Option<Map<char>>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    /// Number of frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Check if recording is empty.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Add a frame.
+    pub fn push(&mut self, frame: Map<char>) {
+        let diff = self.last.as_ref().and_then(|last| {
+            ((last.h, last.w) == (frame.h, frame.w)).then(|| {
+                last.iter_values()
+                    .zip(frame.iter_values())
+                    .enumerate()
+                    .filter(|(_, (a, b))| a != b)
+                    .map(|(i, (_, b))| (i, *b))
+                    .collect()
+            })
+        });
+        self.frames.push(match diff {
+            Some(diff) => Frame::Diff(diff),
+            None => Frame::Key(frame.clone()),
+        });
+        self.last = Some(frame);
+    }
+
+    /// Iterate over reconstructed frames.
+    pub fn iter(&self) -> impl Iterator<Item = Map<char>> + '_ {
+        let mut cur: Option<Map<char>> = None;
+        self.frames.iter().map(move |frame| {
+            match frame {
+                Frame::Key(map) => cur = Some(map.clone()),
+                Frame::Diff(diff) => {
+                    let map = cur.as_mut().unwrap();
+                    let w = map.w;
+                    for (i, c) in diff {
+                        map[(i / w, i % w)] = *c;
+                    }
+                }
+            }
+            cur.clone().unwrap()
+        })
+    }
+
+    /// Play frames as an ANSI terminal animation.
+    pub fn play<W: Write>(&self, out: &mut W, delay: Duration) -> io::Result<()> {
+        for (i, frame) in self.iter().enumerate() {
+            write!(out, "\x1b[H\x1b[2J{frame}\nframe {i}\n")?;
+            out.flush()?;
+            sleep(delay);
+        }
+        Ok(())
+    }
+
+    /// Write frames as numbered PPM files `frame_00000.ppm`, ... into dir.
+    pub fn save_ppm_sequence<P: AsRef<Path>>(&self, dir: P, scale: usize) -> io::Result<()> {
+        fs::create_dir_all(&dir)?;
+        for (i, frame) in self.iter().enumerate() {
+            let path = dir.as_ref().join(format!("frame_{i:05}.ppm"));
+            Canvas::from_map(&frame, |c| char_color(*c))
+                .upscale(scale)
+                .save(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Default palette: light background, dark walls and distinct colors for other characters.
+fn char_color(c: char) -> [u8; 3] {
+    const PALETTE: [[u8; 3]; 8] = [
+        [230, 25, 75],
+        [60, 180, 75],
+        [0, 130, 200],
+        [245, 130, 48],
+        [145, 30, 180],
+        [70, 240, 240],
+        [240, 50, 230],
+        [210, 245, 60],
+    ];
+    match c {
+        '.' | ' ' => [255, 255, 255],
+        '#' => [40, 40, 40],
+        c => PALETTE[c as usize % PALETTE.len()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording() {
+        let mut recording = Recording::new();
+        let a: Map<char> = "..\n.#".parse().unwrap();
+        let b: Map<char> = "@.\n.#".parse().unwrap();
+        let c: Map<char> = "...".parse().unwrap();
+        recording.push(a.clone());
+        recording.push(b.clone());
+        recording.push(c.clone());
+
+        assert_eq!(recording.len(), 3);
+        assert!(matches!(recording.frames[1], Frame::Diff(ref d) if d.len() == 1));
+        assert!(matches!(recording.frames[2], Frame::Key(_)));
+
+        let frames = recording.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        assert_eq!(frames, vec![a.to_string(), b.to_string(), c.to_string()]);
+    }
+
+    #[test]
+    fn test_play() {
+        let mut recording = Recording::new();
+        recording.push("ab".parse().unwrap());
+        let mut out = Vec::new();
+        recording.play(&mut out, Duration::ZERO).unwrap();
+        assert_eq!(out, b"\x1b[H\x1b[2Jab\nframe 0\n");
+    }
+}
@@ -0,0 +1,249 @@
+use crate::vec2::{Vec2i, Vec3i};
+
+/// Axis-aligned rectangle of lattice points [min.x, max.x) x [min.y, max.y).
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Rect {
+    pub min: Vec2i,
+    pub max: Vec2i,
+}
+
+/// Axis-aligned box of lattice points [min, max) along each of x, y and z.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Cuboid {
+    pub min: Vec3i,
+    pub max: Vec3i,
+}
+
+/// Operations shared by Rect and Cuboid.
+macro_rules! impl_box {
+    ($B:ident, $V:ident, $($c:ident),+) => {
+        impl $B {
+            pub const fn new(min: $V, max: $V) -> Self {
+                $B { min, max }
+            }
+
+            /// Smallest box that contains all points, None if there are no points.
+            pub fn from_points<'a, I>(points: I) -> Option<Self>
+            where
+                I: IntoIterator<Item = &'a $V>,
+            {
+                let mut it = points.into_iter();
+                let first = *it.next()?;
+                let (min, max) = it.fold((first, first), |(lo, hi), p| (lo.component_min(*p), hi.component_max(*p)));
+                Some($B::new(min, max + 1))
+            }
+
+            /// Check if box contains no points.
+            pub fn is_empty(&self) -> bool {
+                false $(|| self.min.$c >= self.max.$c)+
+            }
+
+            /// Size along each axis (zero for empty boxes).
+            pub fn size(&self) -> $V {
+                (self.max - self.min).component_max($V::default())
+            }
+
+            pub fn contains(&self, p: &$V) -> bool {
+                true $(&& self.min.$c <= p.$c && p.$c < self.max.$c)+
+            }
+
+            /// Check if other lies completely inside self.
+            pub fn contains_box(&self, other: &Self) -> bool {
+                other.is_empty() || (true $(&& self.min.$c <= other.min.$c && other.max.$c <= self.max.$c)+)
+            }
+
+            /// Overlap of two boxes, None if they don't overlap.
+            pub fn intersect(&self, other: &Self) -> Option<Self> {
+                let ret = $B::new(self.min.component_max(other.min), self.max.component_min(other.max));
+                (!ret.is_empty()).then_some(ret)
+            }
+
+            /// Smallest box that contains both boxes.
+            pub fn union_bbox(&self, other: &Self) -> Self {
+                match (self.is_empty(), other.is_empty()) {
+                    (true, _) => *other,
+                    (_, true) => *self,
+                    _ => $B::new(self.min.component_min(other.min), self.max.component_max(other.max)),
+                }
+            }
+
+            /// Closest point inside the (non-empty) box.
+            pub fn clamp(&self, p: &$V) -> $V {
+                assert!(!self.is_empty());
+                $V { $($c: p.$c.clamp(self.min.$c, self.max.$c - 1)),+ }
+            }
+
+            /// Points of self that are not in other, as disjoint boxes.
+            ///
+            /// Slabs are cut off axis by axis, so there are at most two pieces per axis.
+            #[allow(unused_assignments)] // rest is not read after the last axis
+            pub fn subtract(&self, other: &Self) -> Vec<Self> {
+                let Some(cut) = self.intersect(other) else {
+                    return if self.is_empty() { vec![] } else { vec![*self] };
+                };
+                let mut ret = Vec::new();
+                let mut rest = *self;
+                $(
+                    if rest.min.$c < cut.min.$c {
+                        let mut piece = rest;
+                        piece.max.$c = cut.min.$c;
+                        rest.min.$c = cut.min.$c;
+                        ret.push(piece);
+                    }
+                    if cut.max.$c < rest.max.$c {
+                        let mut piece = rest;
+                        piece.min.$c = cut.max.$c;
+                        rest.max.$c = cut.max.$c;
+                        ret.push(piece);
+                    }
+                )+
+                ret
+            }
+        }
+    };
+}
+
+impl_box!(Rect, Vec2i, x, y);
+impl_box!(Cuboid, Vec3i, x, y, z);
+
+impl Rect {
+    /// Rectangle with top-left corner at origin and size h x w.
+    pub fn from_size(origin: &Vec2i, h: usize, w: usize) -> Self {
+        Rect::new(*origin, *origin + Vec2i::new(w as i64, h as i64))
+    }
+
+    pub fn width(&self) -> usize {
+        self.size().x as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.size().y as usize
+    }
+
+    /// Number of points.
+    pub fn area(&self) -> u64 {
+        let s = self.size();
+        (s.x * s.y) as u64
+    }
+
+    /// Split into NW, NE, SW and SE quadrants.
+    ///
+    /// For odd sizes the middle row or column belongs to no quadrant.
+    pub fn split_quadrants(&self) -> [Rect; 4] {
+        let s = self.size();
+        let (x0, y0) = (self.min.x + s.x / 2, self.min.y + s.y / 2);
+        let (x1, y1) = (self.max.x - s.x / 2, self.max.y - s.y / 2);
+        [
+            Rect::new(self.min, Vec2i::new(x0, y0)),
+            Rect::new(Vec2i::new(x1, self.min.y), Vec2i::new(self.max.x, y0)),
+            Rect::new(Vec2i::new(self.min.x, y1), Vec2i::new(x0, self.max.y)),
+            Rect::new(Vec2i::new(x1, y1), self.max),
+        ]
+    }
+
+    /// Iterate over points in row-major order.
+    pub fn iter_points(&self) -> impl Iterator<Item = Vec2i> {
+        let (min, max) = (self.min, self.max);
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| Vec2i::new(x, y)))
+    }
+}
+
+impl Cuboid {
+    /// Number of points.
+    pub fn volume(&self) -> u64 {
+        let s = self.size();
+        (s.x * s.y * s.z) as u64
+    }
+
+    /// Iterate over points with x varying fastest.
+    pub fn iter_points(&self) -> impl Iterator<Item = Vec3i> {
+        let (min, max) = (self.min, self.max);
+        (min.z..max.z).flat_map(move |z| {
+            (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| Vec3i::new(x, y, z)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect() {
+        let r = Rect::from_size(&Vec2i::new(1, 2), 3, 4);
+        assert_eq!((r.height(), r.width(), r.area()), (3, 4, 12));
+        assert!(r.contains(&Vec2i::new(4, 4)));
+        assert!(!r.contains(&Vec2i::new(5, 4)));
+        assert_eq!(r.iter_points().count(), 12);
+        assert!(r.iter_points().all(|p| r.contains(&p)));
+        assert_eq!(r.clamp(&Vec2i::new(-5, 10)), Vec2i::new(1, 4));
+
+        let pts = [Vec2i::new(3, -1), Vec2i::new(-2, 4), Vec2i::new(0, 0)];
+        let bbox = Rect::from_points(&pts).unwrap();
+        assert_eq!(bbox, Rect::new(Vec2i::new(-2, -1), Vec2i::new(4, 5)));
+        assert!(Rect::from_points(&[]).is_none());
+
+        let s = Rect::new(Vec2i::new(0, 0), Vec2i::new(3, 3));
+        assert_eq!(
+            r.intersect(&s),
+            Some(Rect::new(Vec2i::new(1, 2), Vec2i::new(3, 3)))
+        );
+        assert_eq!(
+            r.union_bbox(&s),
+            Rect::new(Vec2i::new(0, 0), Vec2i::new(5, 5))
+        );
+        assert_eq!(s.intersect(&Rect::from_size(&Vec2i::new(3, 0), 1, 1)), None);
+        assert!(r.union_bbox(&s).contains_box(&r));
+        assert!(!bbox.contains_box(&r));
+    }
+
+    #[test]
+    fn test_quadrants() {
+        let r = Rect::from_size(&Vec2i::new(0, 0), 7, 11);
+        let q = r.split_quadrants();
+        assert!(q.iter().all(|q| q.height() == 3 && q.width() == 5));
+        assert!(!q.iter().any(|q| q.contains(&Vec2i::new(5, 0))));
+        assert!(!q.iter().any(|q| q.contains(&Vec2i::new(0, 3))));
+        assert!(q[3].contains(&Vec2i::new(10, 6)));
+
+        let r = Rect::from_size(&Vec2i::new(0, 0), 4, 4);
+        let total: u64 = r.split_quadrants().iter().map(Rect::area).sum();
+        assert_eq!(total, r.area());
+    }
+
+    #[test]
+    fn test_subtract() {
+        let a = Rect::from_size(&Vec2i::new(0, 0), 5, 6);
+        for b in [
+            Rect::from_size(&Vec2i::new(2, 1), 2, 2),
+            Rect::from_size(&Vec2i::new(-1, -1), 3, 10),
+            Rect::from_size(&Vec2i::new(10, 10), 1, 1),
+            a,
+        ] {
+            let pieces = a.subtract(&b);
+            for p in Rect::new(Vec2i::new(-2, -2), Vec2i::new(8, 8)).iter_points() {
+                let n = pieces.iter().filter(|r| r.contains(&p)).count();
+                assert_eq!(n, (a.contains(&p) && !b.contains(&p)) as usize);
+            }
+        }
+        assert_eq!(
+            a.subtract(&Rect::from_size(&Vec2i::new(2, 1), 2, 2)).len(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_cuboid() {
+        let a = Cuboid::new(Vec3i::new(0, 0, 0), Vec3i::new(3, 3, 3));
+        let b = Cuboid::new(Vec3i::new(1, 1, 1), Vec3i::new(2, 2, 2));
+        assert_eq!(a.volume(), 27);
+        assert_eq!(a.iter_points().count(), 27);
+        let pieces = a.subtract(&b);
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces.iter().map(Cuboid::volume).sum::<u64>(), 26);
+        assert_eq!(a.intersect(&b), Some(b));
+        assert_eq!(a.clamp(&Vec3i::new(5, -1, 1)), Vec3i::new(2, 0, 1));
+        let bbox = Cuboid::from_points(&[Vec3i::new(0, 0, 0), Vec3i::new(2, 2, 2)]);
+        assert_eq!(bbox, Some(a));
+    }
+}
@@ -26,9 +26,8 @@ pub fn part_b(input: &str) -> Answer {
 
     let mut result = 0;
     for x in data.iter().map(|v| v[0]) {
-        let maybe_count = counter.get(&x);
-        if maybe_count.is_some() {
-            result += x * maybe_count.unwrap();
+        if let Some(count) = counter.get(&x) {
+            result += x * count;
         }
     }
     Answer::Number(result)
@@ -1,8 +1,8 @@
-use crate::map2d::Map;
+use crate::jump_table::JumpTable;
+use crate::map2d::{BitMap, DirBitMap, GridTile, Map};
+use crate::recorder;
 use crate::vec2::{Dir, Vec2i};
 use crate::Answer;
-use bitvec::prelude::*;
-use itertools::iproduct;
 use rayon::prelude::*;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -17,29 +17,32 @@ enum Tile {
     Obstacle,
 }
 
-type State = (Vec2i, Dir);
-
-fn parse_board(chars: &[&str]) -> (State, Map<Tile>) {
-    let parse_tile = |c: char| -> Tile {
+impl GridTile for Tile {
+    fn from_char(c: char) -> Result<Self, String> {
         match c {
-            '.' => Tile::Free(Visited::N),
-            '#' => Tile::Obstacle,
-            '^' => Tile::Free(Visited::N),
-            _ => panic!("Unknown tile '{}'", c),
+            '.' => Ok(Tile::Free(Visited::N)),
+            '#' => Ok(Tile::Obstacle),
+            '^' => Ok(Tile::Free(Visited::N)),
+            _ => Err("Unknown tile".to_string()),
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Tile::Free(Visited::N) => '.',
+            Tile::Free(Visited::Y) => 'X',
+            Tile::Obstacle => '#',
         }
-    };
-    let map = Map::from_vecs(
-        chars
-            .iter()
-            .map(|vec| vec.chars().map(parse_tile).collect::<Vec<_>>())
-            .collect::<Vec<_>>(),
-    );
-
-    let (r, c) = iproduct!(0..chars.len(), 0..chars[0].len())
-        .find(|(r, c)| chars[*r].as_bytes()[*c] == b'^')
-        .unwrap();
-
-    ((Vec2i::new(c as i64, r as i64), Dir::N), map)
+    }
+}
+
+type State = (Vec2i, Dir);
+
+fn parse_board(input: &str) -> (State, Map<Tile>) {
+    let chars: Map<char> = input.trim().parse().unwrap();
+    let start = chars.find_unique(&'^').unwrap();
+    let map = chars.map_values(|c| Tile::from_char(*c).unwrap());
+    ((start, Dir::N), map)
 }
 
 // Make one step.
@@ -62,14 +65,13 @@ fn step(state: &State, map: &Map<Tile>, extra_obs: Option<&Vec2i>) -> Option<Sta
 }
 
 pub fn part_a(input: &str) -> Answer {
-    let chars: Vec<_> = input.lines().collect();
-    let (state_inner, map) = parse_board(&chars);
+    let (state_inner, map) = parse_board(input);
     let mut state: Option<State> = Some(state_inner);
 
-    let mut visited = bitvec![0; 130 * 130];
+    let mut visited = BitMap::same_size_as(&map);
 
     while let Some((pos, _)) = state {
-        visited.set(pos.linear_idx(130), true);
+        visited.insert(&pos);
         state = state.and_then(|s| step(&s, &map, None));
     }
 
@@ -77,29 +79,22 @@ pub fn part_a(input: &str) -> Answer {
     Answer::Number(res as i64)
 }
 
-// Step until next obstacle and turn.
+// Jump to next obstacle and turn.
 // Return None if we step outside of map.
-fn quick_step(state: &State, map: &Map<Tile>, extra_obs: &Vec2i) -> Option<State> {
-    let mut pos = state.0;
-    while let Some(pos_new) = map.step_within(&pos, state.1, 1) {
-        if pos_new == *extra_obs || map[&pos_new] == Tile::Obstacle {
-            return Some((pos, state.1.turn_right()));
-        }
-        pos = pos_new;
-    }
-    None
+fn quick_step(state: &State, jumps: &JumpTable, extra_obs: &Vec2i) -> Option<State> {
+    let (pos, dir) = *state;
+    let obs = jumps.next_blocker_with(&pos, dir, extra_obs)?;
+    Some((obs.step(-dir, 1), dir.turn_right()))
 }
 
-fn has_loop(state0: &State, map: &Map<Tile>, extra_obs: &Vec2i) -> bool {
+fn has_loop(state0: &State, map: &Map<Tile>, jumps: &JumpTable, extra_obs: &Vec2i) -> bool {
     let mut state: Option<State> = Some(*state0);
-    let mut visited = bitvec![0; 130 * 130 * 4];
+    let mut visited = DirBitMap::same_size_as(map);
     while let Some((pos, dir)) = state {
-        let state_idx = 4 * pos.linear_idx(130) + dir as usize;
-        if visited[state_idx] {
+        if !visited.insert(&pos, dir) {
             return true;
         }
-        visited.set(state_idx, true);
-        state = state.and_then(|s| quick_step(&s, map, extra_obs));
+        state = state.and_then(|s| quick_step(&s, jumps, extra_obs));
     }
     false
 }
@@ -108,8 +103,7 @@ pub fn part_b(input: &str) -> Answer {
     // Follow path as in part_a. At each state where there is no obstacle,
     // put an obstacle in front of the guard and unroll to see if we create a loop.
 
-    let chars: Vec<_> = input.lines().collect();
-    let (state0, mut map) = parse_board(&chars);
+    let (state0, mut map) = parse_board(input);
     let mut state: Option<State> = Some(state0);
     let mut maybe_prev: Option<State> = None;
 
@@ -123,13 +117,19 @@ pub fn part_b(input: &str) -> Answer {
         }
         // update state
         map[&pos] = Tile::Free(Visited::Y);
+        recorder::record_with(|| {
+            let mut frame = map.map_values(Tile::to_char);
+            frame[&pos] = '^';
+            frame
+        });
         (maybe_prev, state) = (state, state.as_ref().and_then(|s| step(s, &map, None)));
     }
 
     // check each candidate for a loop in parallel
+    let jumps = JumpTable::from_map(&map, |t| *t == Tile::Obstacle);
     let res = candidate_loops
         .par_iter()
-        .filter(|(s0, obs_pos)| has_loop(s0, &map, obs_pos))
+        .filter(|(s0, obs_pos)| has_loop(s0, &map, &jumps, obs_pos))
         .count();
 
     Answer::Number(res as i64)
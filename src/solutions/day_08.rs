@@ -1,6 +1,8 @@
+use crate::geometry::line_points_in;
 use crate::hash::{FxHashMap, FxHashMapBuilder};
+use crate::rect::Rect;
 use crate::vec2::Vec2i;
-use crate::{math::gcd, Answer};
+use crate::Answer;
 use itertools::Itertools;
 
 type Groups = FxHashMap<char, Vec<Vec2i>>;
@@ -15,7 +17,7 @@ fn parse_input(input: &str) -> (Groups, usize, usize) {
             .flat_map(move |(j, c)| -> Option<(char, Vec2i)> {
                 match c {
                     '.' => None,
-                    _ => Some((c, Vec2i::new(i as i64, j as i64))),
+                    _ => Some((c, Vec2i::from_yx((i, j)))),
                 }
             })
     });
@@ -31,14 +33,15 @@ fn parse_input(input: &str) -> (Groups, usize, usize) {
 pub fn part_a(input: &str) -> Answer {
     let (groups, h, w) = parse_input(input);
     let res = groups
-        .iter()
-        .flat_map(|(_, group)| find_antinodes::<true>(group, h, w))
+        .values()
+        .flat_map(|group| find_antinodes::<true>(group, h, w))
         .unique()
         .count();
     Answer::Number(res as i64)
 }
 
 fn find_antinodes<const PARTA: bool>(group: &[Vec2i], h: usize, w: usize) -> Vec<Vec2i> {
+    let bounds = Rect::from_size(&Vec2i::new(0, 0), h, w);
     let pairs_iter =
         (0..group.len()).flat_map(|i| (i + 1..group.len()).map(move |j| (&group[i], &group[j])));
 
@@ -48,23 +51,11 @@ fn find_antinodes<const PARTA: bool>(group: &[Vec2i], h: usize, w: usize) -> Vec
                 let dp = *p - *q;
                 [*p + dp, *q - dp]
             })
-            .filter(|p| p.is_in_grid(h, w))
+            .filter(|p| bounds.contains(p))
             .collect()
     } else {
         pairs_iter
-            .flat_map(|(p, q)| {
-                let mut dp = *p - *q;
-                let t = gcd(dp.x.unsigned_abs(), dp.y.unsigned_abs()) as i64;
-                dp /= t;
-                (0..)
-                    .map(move |k| -> Vec2i { *p + dp * k })
-                    .take_while(|p| p.is_in_grid(h, w))
-                    .chain(
-                        (1..)
-                            .map(move |k| -> Vec2i { *p - dp * k })
-                            .take_while(|p| p.is_in_grid(h, w)),
-                    )
-            })
+            .flat_map(|(p, q)| line_points_in(p, q, &bounds))
             .collect()
     }
 }
@@ -72,8 +63,8 @@ fn find_antinodes<const PARTA: bool>(group: &[Vec2i], h: usize, w: usize) -> Vec
 pub fn part_b(input: &str) -> Answer {
     let (groups, h, w) = parse_input(input);
     let res = groups
-        .iter()
-        .flat_map(|(_, group)| find_antinodes::<false>(group, h, w))
+        .values()
+        .flat_map(|group| find_antinodes::<false>(group, h, w))
         .unique()
         .count();
     Answer::Number(res as i64)
@@ -1,3 +1,4 @@
+use crate::interval::IntervalSet;
 use crate::Answer;
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -102,66 +103,26 @@ pub fn part_a(input: &str) -> Answer {
     Answer::Number(ret as i64)
 }
 
-#[derive(Debug)]
-struct FreeSpaceHolder {
-    /// Keep track of free space. Each entry holds free space of a given size sorted in reverse by
-    /// position.
-    pub data: Vec<Vec<Slot>>,
-}
-
-impl FreeSpaceHolder {
-    fn new(slots: &[Slot]) -> Self {
-        let mut data: Vec<Vec<Slot>> = vec![vec![]; 10];
-        for slot in slots.iter().rev() {
-            if slot.is_free() {
-                data[slot.len].push(*slot);
-            }
-        }
-        Self { data }
-    }
-
-    fn use_freespace(&mut self, pos: usize, cap: usize) -> Option<usize> {
-        let stack_to_use = (cap..10)
-            .filter_map(|len| self.data[len].last())
-            .filter(|slot| slot.pos < pos)
-            .min_by_key(|slot| slot.pos)
-            .map(|slot| slot.len);
-
-        stack_to_use.map(|stack_idx| {
-            let Slot { pos, len, kind: _ } = self.data[stack_idx].pop().unwrap();
-            if len > cap {
-                let new_len = len - cap;
-                self.data[new_len].push(Slot {
-                    pos: pos + cap,
-                    len: new_len,
-                    kind: SlotKind::Free,
-                });
-                // ensure it's sorted by bubbling up the new element
-                let mut idx = self.data[new_len].len() - 1;
-                while idx > 1 && self.data[new_len][idx - 1].pos < self.data[new_len][idx].pos {
-                    self.data[new_len].swap(idx - 1, idx);
-                    idx -= 1;
-                }
-            }
-            pos
-        })
-    }
-}
-
 pub fn part_b(input: &str) -> Answer {
     let input = parse_input(input);
-    let mut fsp = FreeSpaceHolder::new(&input);
+    let mut used: IntervalSet<usize> = input
+        .iter()
+        .filter(|slot| !slot.is_free())
+        .map(|slot| slot.pos..slot.pos + slot.len)
+        .collect();
 
     let mut ret: usize = 0;
-    for rght in (0..input.len()).rev() {
-        if input[rght].is_free() {
-            continue;
-        }
-        let len = input[rght].len;
-        let pos = fsp
-            .use_freespace(input[rght].pos, len)
-            .unwrap_or(input[rght].pos);
-        ret += input[rght].file_id() as usize * (pos * len + len * (len - 1) / 2);
+    for slot in input.iter().rev().filter(|slot| !slot.is_free()) {
+        let Slot { pos, len, .. } = *slot;
+        let new_pos = match used.first_gap(len, 0..pos) {
+            Some(new_pos) => {
+                used.remove(pos..pos + len);
+                used.insert(new_pos..new_pos + len);
+                new_pos
+            }
+            None => pos,
+        };
+        ret += slot.file_id() as usize * (new_pos * len + len * (len - 1) / 2);
     }
 
     Answer::Number(ret as i64)
@@ -1,13 +1,10 @@
-use crate::map2d::Map;
+use crate::map2d::{BitMap, Map};
 use crate::vec2::{Dir, Vec2i};
 use crate::Answer;
-use bitvec::prelude::*;
-
-const DIRECTIONS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
 
 fn n_peaks_from_trailhead<const PARTB: bool>(trail_head: Vec2i, map: &Map<u32>) -> i64 {
     let mut stack = vec![trail_head];
-    let mut peaks = bitvec![0; map.h * map.w];
+    let mut peaks = BitMap::same_size_as(map);
     let mut n_dist = 0;
 
     while let Some(cur) = stack.pop() {
@@ -16,11 +13,11 @@ fn n_peaks_from_trailhead<const PARTB: bool>(trail_head: Vec2i, map: &Map<u32>)
                 if PARTB {
                     n_dist += 1;
                 } else {
-                    peaks.set(cur.linear_idx(map.w), true);
+                    peaks.insert(&cur);
                 }
             }
             d => {
-                let new_pos = DIRECTIONS
+                let new_pos = Dir::all()
                     .iter()
                     .map(|d| cur.step(*d, 1))
                     .filter(|p| map.get(p).map(|x| *x == d + 1).unwrap_or_default())
@@ -38,12 +35,7 @@ fn n_peaks_from_trailhead<const PARTB: bool>(trail_head: Vec2i, map: &Map<u32>)
 }
 
 pub fn part_a(input: &str) -> Answer {
-    let map = Map::from_iterators(
-        input
-            .trim()
-            .lines()
-            .map(|l| l.chars().map(|c| c as u32 - '0' as u32)),
-    );
+    let map: Map<u32> = input.trim().parse().unwrap();
 
     let ret = map
         .iter()
@@ -55,12 +47,7 @@ pub fn part_a(input: &str) -> Answer {
 }
 
 pub fn part_b(input: &str) -> Answer {
-    let map = Map::from_iterators(
-        input
-            .trim()
-            .lines()
-            .map(|l| l.chars().map(|c| c as u32 - '0' as u32)),
-    );
+    let map: Map<u32> = input.trim().parse().unwrap();
 
     let ret = map
         .iter()
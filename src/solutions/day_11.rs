@@ -14,7 +14,7 @@ fn transform_number_counter(counter: &NumberCounter) -> NumberCounter {
             0 => {
                 *ret.entry(1).or_default() += c;
             }
-            x if nl % 2 == 0 => {
+            x if nl.is_multiple_of(2) => {
                 let tmp = 10u64.pow(nl as u32 / 2) as usize;
                 *ret.entry(x / tmp).or_default() += c;
                 *ret.entry(x % tmp).or_default() += c;
@@ -39,7 +39,7 @@ fn count_number_with_mem(x: usize, i: usize, mem: &mut FxHashMap<(usize, usize),
     let nl = number_length(x as u64);
     let res = match x {
         0 => count_number_with_mem(1, i - 1, mem),
-        x if nl % 2 == 0 => {
+        x if nl.is_multiple_of(2) => {
             let tmp = 10u64.pow(nl as u32 / 2) as usize;
             count_number_with_mem(x / tmp, i - 1, mem) + count_number_with_mem(x % tmp, i - 1, mem)
         }
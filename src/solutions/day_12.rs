@@ -1,14 +1,11 @@
 use std::iter::successors;
 
-use crate::hash::{FxHashSet, FxHashSetBuilder};
-use crate::map2d::Map;
+use crate::map2d::{BitMap, DirBitMap, Map};
 use crate::vec2::{Dir, Vec2i};
 use crate::Answer;
 
 type PosDir = (Vec2i, Dir);
 
-const DIRECTIONS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
-
 /// Take a step along contour of region defined by f_pred.
 /// Assumes that s is on the contour pointing in the ccw direction.
 fn step_contour_ccw<T, F>(s: &PosDir, map: &Map<T>, f_pred: &F) -> PosDir
@@ -35,7 +32,7 @@ fn trace_contour<F>(
     s0: &PosDir,
     map: &Map<char>,
     f_pred: &F,
-    contour_visited: &mut FxHashSet<PosDir>,
+    contour_visited: &mut DirBitMap,
 ) -> u32
 where
     F: Fn(&char) -> bool,
@@ -50,7 +47,7 @@ where
         }
     })
     .for_each(|(_, (p, d))| {
-        contour_visited.insert((p, d));
+        contour_visited.insert(&p, d);
         if d != d_last {
             perimiter += 1;
         }
@@ -63,15 +60,15 @@ where
 pub fn solve<const PARTB: bool>(input: &str) -> Answer {
     let map = Map::from_iterators(input.trim().lines().map(|s| s.chars()));
 
-    let mut cell_visited: FxHashSet<Vec2i> = FxHashSet::with_capacity(141 * 141);
-    let mut contour_visited: FxHashSet<PosDir> = FxHashSet::with_capacity(141 * 141 * 4);
+    let mut cell_visited = BitMap::same_size_as(&map);
+    let mut contour_visited = DirBitMap::same_size_as(&map);
     let mut stack: Vec<(Dir, Vec2i)> = Vec::with_capacity(141 * 141 * 4);
     let mut result = 0;
 
     for (coord, v) in map.iter() {
         let f_region = |c: &char| *c == *v;
 
-        if !cell_visited.contains(&coord) {
+        if cell_visited.get(&coord) == Some(false) {
             stack.clear();
             let mut area = 0;
             let mut perimiter = 0;
@@ -81,17 +78,16 @@ pub fn solve<const PARTB: bool>(input: &str) -> Answer {
                     // stepped outside region, increase perimiter
                     if PARTB {
                         let s0 = (cur.step(-dir, 1), dir.turn_left());
-                        if !contour_visited.contains(&s0) {
+                        if contour_visited.get(&s0.0, s0.1) == Some(false) {
                             perimiter += trace_contour(&s0, &map, &f_region, &mut contour_visited);
                         }
                     } else {
                         perimiter += 1;
                     }
-                } else if !cell_visited.contains(&cur) {
+                } else if cell_visited.insert(&cur) {
                     // still inside region, recurse
-                    cell_visited.insert(cur);
                     area += 1;
-                    stack.extend(DIRECTIONS.map(|dir| (dir, cur.step(dir, 1))));
+                    stack.extend(Dir::all().map(|dir| (dir, cur.step(dir, 1))));
                 }
             }
             result += (area * perimiter) as i64;
@@ -1,5 +1,7 @@
 use crate::map2d::Map;
 use crate::math::crt2;
+use crate::recorder;
+use crate::rect::Rect;
 use crate::{vec2::Vec2i, Answer};
 
 const H: usize = 103;
@@ -30,51 +32,34 @@ fn parse_robot(line: &str) -> Robot {
 }
 
 fn simulate_robot(p: Vec2i, v: Vec2i, h: usize, w: usize, t: usize) -> Vec2i {
-    let mut pn = p + v * t as i64;
-    pn.x = pn.x.rem_euclid(w as i64);
-    pn.y = pn.y.rem_euclid(h as i64);
-    pn
+    (p + v * t as i64).wrap_in_grid(h, w)
 }
 
 fn solve_part_a(input: &str, h: usize, w: usize) -> Answer {
-    let mut n_tl = 0;
-    let mut n_tr = 0;
-    let mut n_br = 0;
-    let mut n_bl = 0;
+    let quadrants = Rect::from_size(&Vec2i::new(0, 0), h, w).split_quadrants();
+    let mut counts = [0; 4];
     for robot in input.trim().lines().map(parse_robot) {
         let new_pos = simulate_robot(robot.p, robot.v, h, w, 100);
-        let top = new_pos.y < h as i64 / 2;
-        let bot = new_pos.y > h as i64 / 2;
-        let left = new_pos.x < w as i64 / 2;
-        let rght = new_pos.x > w as i64 / 2;
-        if top & left {
-            n_tl += 1;
-        } else if top & rght {
-            n_tr += 1;
-        } else if rght & bot {
-            n_br += 1;
-        } else if bot & left {
-            n_bl += 1;
+        if let Some(i) = quadrants.iter().position(|q| q.contains(&new_pos)) {
+            counts[i] += 1;
         }
     }
 
-    Answer::Number(n_tl * n_tr * n_br * n_bl)
+    Answer::Number(counts.iter().product())
 }
 
 pub fn part_a(input: &str) -> Answer {
     solve_part_a(input, H, W)
 }
 
-fn plot_robots(positions: &[Vec2i], h: usize, w: usize) {
+fn plot_robots(positions: &[Vec2i], h: usize, w: usize) -> Map<char> {
     let mut picture = Map::<char>::new_constant(h, w, ' ');
     for p in positions.iter() {
         picture[p] = 'x';
     }
-    println!("{}", picture);
+    picture
 }
 
-const PLOT: bool = false;
-
 pub fn part_b(input: &str) -> Answer {
     // observed two series that show "patterns":
     // 65 + 103 * i
@@ -83,14 +68,16 @@ pub fn part_b(input: &str) -> Answer {
     //  x % 103 = 65
     //  x % 101 = 9
     let t = crt2(103, 65, 101, 9).unwrap();
-    if PLOT {
-        let positions = input
-            .trim()
-            .lines()
-            .map(parse_robot)
-            .map(|r| simulate_robot(r.p, r.v, 103, 101, t as usize))
-            .collect::<Vec<_>>();
-        plot_robots(&positions, H, W);
+    if recorder::is_enabled() {
+        // record the seconds around the tree
+        let robots = input.trim().lines().map(parse_robot).collect::<Vec<_>>();
+        for ti in t - 5..=t + 5 {
+            let positions = robots
+                .iter()
+                .map(|r| simulate_robot(r.p, r.v, H, W, ti as usize))
+                .collect::<Vec<_>>();
+            recorder::record(&plot_robots(&positions, H, W));
+        }
     }
     Answer::Number(t)
 }
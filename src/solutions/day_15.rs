@@ -1,7 +1,7 @@
 use crate::hash::{FxHashSet, FxHashSetBuilder};
-use std::fmt;
 
-use crate::map2d::Map;
+use crate::map2d::{GridTile, Map};
+use crate::recorder;
 use crate::vec2::{Dir, Vec2i};
 use crate::Answer;
 
@@ -19,29 +19,26 @@ enum Tile {
     Robot,
 }
 
-impl fmt::Display for Tile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let o = match &self {
+impl GridTile for Tile {
+    fn from_char(c: char) -> Result<Self, String> {
+        match c {
+            '#' => Ok(Self::Wall),
+            'O' => Ok(Self::Box(BoxSide::L)),
+            '[' => Ok(Self::Box(BoxSide::L)),
+            ']' => Ok(Self::Box(BoxSide::R)),
+            '.' => Ok(Self::Free),
+            '@' => Ok(Self::Robot),
+            _ => Err("Invalid tile".to_string()),
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match &self {
             Self::Wall => '#',
             Self::Box(BoxSide::L) => '[',
             Self::Box(BoxSide::R) => ']',
             Self::Free => '.',
             Self::Robot => '@',
-        };
-        write!(f, "{}", o)
-    }
-}
-
-impl Tile {
-    pub fn from_char(c: char) -> Self {
-        match c {
-            '#' => Self::Wall,
-            'O' => Self::Box(BoxSide::L),
-            '[' => Self::Box(BoxSide::L),
-            ']' => Self::Box(BoxSide::R),
-            '.' => Self::Free,
-            '@' => Self::Robot,
-            _ => unreachable!("Invalid tile"),
         }
     }
 }
@@ -59,36 +56,37 @@ fn parse_input<const PARTA: bool>(input: &str) -> (Map<Tile>, Vec2i, Vec<Dir>) {
     let map_str = splits.next().unwrap();
     let move_str = splits.next().unwrap();
 
-    let map = if PARTA {
-        Map::from_lines(map_str.lines(), &Tile::from_char)
+    let map: Map<Tile> = if PARTA {
+        map_str.parse().unwrap()
     } else {
-        let iters = map_str.lines().map(|l| {
-            l.chars()
-                .flat_map(|c| match c {
-                    '.' => ['.', '.'],
-                    '#' => ['#', '#'],
-                    'O' => ['[', ']'],
-                    '@' => ['@', '.'],
-                    _ => unreachable!(),
-                })
-                .map(Tile::from_char)
-        });
-        Map::from_iterators(iters)
+        let mut wide = String::with_capacity(2 * map_str.len());
+        for c in map_str.chars() {
+            match c {
+                '.' => wide.push_str(".."),
+                '#' => wide.push_str("##"),
+                'O' => wide.push_str("[]"),
+                '@' => wide.push_str("@."),
+                _ => wide.push(c),
+            }
+        }
+        wide.parse().unwrap()
     };
     let moves = move_str
         .chars()
         .filter_map(Dir::from_char)
         .collect::<Vec<_>>();
 
-    let (robot_tile, _) = map.iter().find(|(_, v)| **v == Tile::Robot).unwrap();
+    let robot_tile = map.find_unique(&Tile::Robot).unwrap();
 
     (map, robot_tile, moves)
 }
 
 fn solve<const PARTA: bool>(input: &str) -> Answer {
     let (mut map, mut state, moves) = parse_input::<PARTA>(input);
+    recorder::record(&map);
     moves.iter().for_each(|m| {
         step::<PARTA>(&mut state, &mut map, *m);
+        recorder::record(&map);
     });
     let result = map
         .iter()
@@ -253,6 +251,27 @@ mod tests {
         assert_eq!(result, Answer::Number(2028));
     }
 
+    #[test]
+    fn test_final_map_a_s() {
+        let (mut map, mut state, moves) = parse_input::<true>(TEST_INPUT_S1);
+        for m in moves {
+            step::<true>(&mut state, &mut map, m);
+        }
+        let expected: Map<Tile> = indoc! {"
+            ########
+            #....OO#
+            ##.....#
+            #.....O#
+            #.#O@..#
+            #...O..#
+            #...O..#
+            ########
+        "}
+        .parse()
+        .unwrap();
+        crate::assert_map_eq!(map, expected);
+    }
+
     #[test]
     fn test_part_b_s() {
         let result = part_b(TEST_INPUT_S2);
@@ -1,8 +1,7 @@
-use crate::container::BucketQueue;
+use crate::container::{MonotoneQueue, RingBuffer};
 use crate::map2d::Map;
 use crate::vec2::{Dir, Vec2i};
 use crate::Answer;
-use std::collections::VecDeque;
 
 #[derive(Clone, Debug, PartialEq)]
 struct State {
@@ -13,18 +12,18 @@ struct State {
 type CostMap = Map<[Option<i64>; 4]>;
 
 pub fn part_a(input: &str) -> Answer {
-    let map = Map::<char>::from_lines(input.trim().lines(), &|c| c);
-    let start = map.iter().find(|(_, v)| **v == 'S').unwrap().0;
-    let end = map.iter().find(|(_, v)| **v == 'E').unwrap().0;
+    let map: Map<char> = input.trim().parse().unwrap();
+    let start = map.find_unique(&'S').unwrap();
+    let end = map.find_unique(&'E').unwrap();
     let cost_map = solve_forward((start, Dir::E), end, &map);
     let optimal_cost = cost_map[&end].iter().flatten().max().unwrap();
     Answer::Number(*optimal_cost)
 }
 
 pub fn part_b(input: &str) -> Answer {
-    let map = Map::<char>::from_lines(input.trim().lines(), &|c| c);
-    let start = map.iter().find(|(_, v)| **v == 'S').unwrap().0;
-    let end = map.iter().find(|(_, v)| **v == 'E').unwrap().0;
+    let map: Map<char> = input.trim().parse().unwrap();
+    let start = map.find_unique(&'S').unwrap();
+    let end = map.find_unique(&'E').unwrap();
     let cost_map = solve_forward((start, Dir::E), end, &map);
     let result = solve_reverse(&cost_map, end);
     Answer::Number(result)
@@ -48,7 +47,7 @@ fn cost_to_go((pos, dir): (Vec2i, Dir), end: Vec2i) -> i64 {
 }
 
 fn solve_forward((pos, dir): (Vec2i, Dir), end: Vec2i, map: &Map<char>) -> CostMap {
-    let mut prio_queue = BucketQueue::new(1001);
+    let mut prio_queue = MonotoneQueue::for_max_weight(1000);
     let mut cost_map = map.same_size_with::<[Option<i64>; 4]>([None, None, None, None]);
     let mut best_cost: Option<u64> = None;
 
@@ -62,12 +61,12 @@ fn solve_forward((pos, dir): (Vec2i, Dir), end: Vec2i, map: &Map<char>) -> CostM
             })
             .unwrap_or_default();
         let is_wall = map[&pos] == '#';
-        let already_visited = cost_map[&pos][dir as usize].is_some();
+        let already_visited = cost_map[&pos][dir].is_some();
         if not_competitive || is_wall || already_visited {
             // do not recurse
             continue;
         }
-        cost_map[&pos][dir as usize] = Some(cost as i64);
+        cost_map[&pos][dir] = Some(cost as i64);
         if pos == end {
             // reached end
             best_cost = best_cost.or(Some(cost));
@@ -108,18 +107,15 @@ fn solve_forward((pos, dir): (Vec2i, Dir), end: Vec2i, map: &Map<char>) -> CostM
 
 fn solve_reverse(cost_map: &CostMap, end: Vec2i) -> i64 {
     // now do a reverse search along all paths that are consistent with the cost-map
-    let mut rqueue: VecDeque<(State, i64)> = VecDeque::new();
+    let mut rqueue: RingBuffer<(State, i64)> = RingBuffer::new();
     let opt_cost = cost_map[&end].iter().flatten().min().unwrap();
-    for dir in [Dir::N, Dir::E, Dir::S, Dir::W] {
+    for dir in Dir::all() {
         rqueue.push_back((State { pos: end, dir }, *opt_cost));
     }
 
     let mut optimal_tiles = cost_map.same_size_with(false);
     while let Some((State { pos, dir }, cost)) = rqueue.pop_front() {
-        if !cost_map[&pos][dir as usize]
-            .map(|c| c == cost)
-            .unwrap_or_default()
-        {
+        if !cost_map[&pos][dir].map(|c| c == cost).unwrap_or_default() {
             continue; // not on an optimal path
         }
 
@@ -155,6 +151,7 @@ fn solve_reverse(cost_map: &CostMap, end: Vec2i) -> i64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::heap::{IndexedMinHeap, MinHeap};
     use indoc::indoc;
 
     const TEST_INPUT: &str = indoc! {"
@@ -218,4 +215,61 @@ mod tests {
         let result = part_b(TEST_INPUT_2);
         assert_eq!(result, Answer::Number(64));
     }
+
+    /// Moves from state (pos, dir) with their cost, states are indexed by 4 * cell + dir.
+    fn moves(map: &Map<char>, s: usize) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let (cell, dir) = (s / 4, Dir::all()[s % 4]);
+        let pos = Vec2i::new((cell % map.w) as i64, (cell / map.w) as i64);
+        let fwd = pos.step(dir, 1);
+        let step = (map[&fwd] != '#').then(|| (4 * fwd.linear_idx(map.w) + dir.index(), 1));
+        let turns = [dir.turn_left(), dir.turn_right()].map(|d| (4 * cell + d.index(), 1000));
+        step.into_iter().chain(turns)
+    }
+
+    fn dijkstra_indexed(map: &Map<char>, start: usize) -> Vec<u64> {
+        let mut dist = vec![u64::MAX; map.h * map.w * 4];
+        let mut heap = IndexedMinHeap::new(dist.len());
+        heap.push_or_decrease(start, 0);
+        while let Some((s, cost)) = heap.pop() {
+            dist[s] = cost;
+            for (t, c) in moves(map, s) {
+                if dist[t] == u64::MAX {
+                    heap.push_or_decrease(t, cost + c);
+                }
+            }
+        }
+        dist
+    }
+
+    fn dijkstra_duplicates(map: &Map<char>, start: usize) -> Vec<u64> {
+        let mut dist = vec![u64::MAX; map.h * map.w * 4];
+        let mut heap = MinHeap::new(|a: &(u64, usize), b: &(u64, usize)| a.cmp(b));
+        heap.push((0, start));
+        while let Some((cost, s)) = heap.pop() {
+            if dist[s] != u64::MAX {
+                continue; // stale entry
+            }
+            dist[s] = cost;
+            for (t, c) in moves(map, s) {
+                if dist[t] == u64::MAX {
+                    heap.push((cost + c, t));
+                }
+            }
+        }
+        dist
+    }
+
+    #[test]
+    fn test_heaps_agree() {
+        for input in [TEST_INPUT, TEST_INPUT_2] {
+            let map: Map<char> = input.trim().parse().unwrap();
+            let start = 4 * map.find_unique(&'S').unwrap().linear_idx(map.w) + Dir::E.index();
+            let indexed = dijkstra_indexed(&map, start);
+            assert_eq!(indexed, dijkstra_duplicates(&map, start));
+
+            let end = map.find_unique(&'E').unwrap().linear_idx(map.w);
+            let best = indexed[4 * end..4 * end + 4].iter().min().unwrap();
+            assert_eq!(Answer::Number(*best as i64), part_a(input));
+        }
+    }
 }
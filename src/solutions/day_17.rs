@@ -13,10 +13,7 @@ fn parse_input(input: &str) -> (Registers, Vec<u64>) {
         lines
             .next()
             .and_then(|l| l.split(": ").nth(1))
-            .and_then(|s: &str| match str::parse::<u64>(s) {
-                Ok(x) => Some(x),
-                Err(_) => None,
-            })
+            .and_then(|s: &str| str::parse::<u64>(s).ok())
             .unwrap()
     };
     let a = reg_parse();
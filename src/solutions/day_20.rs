@@ -1,8 +1,8 @@
-use crate::map2d::Map;
-use crate::vec2::{Vec2i, DIRECTIONS};
+use crate::distance::{distance_field, Metric};
+use crate::map2d::{GridTile, Map};
+use crate::vec2::{Vec2, Vec2i};
 use crate::Answer;
 use rayon::prelude::*;
-use std::collections::VecDeque;
 
 #[derive(Debug, PartialEq)]
 enum Tile {
@@ -12,73 +12,60 @@ enum Tile {
     End,
 }
 
+impl GridTile for Tile {
+    fn from_char(c: char) -> Result<Self, String> {
+        match c {
+            '.' => Ok(Tile::Free),
+            '#' => Ok(Tile::Blocked),
+            'S' => Ok(Tile::Start),
+            'E' => Ok(Tile::End),
+            _ => Err("Invalid tile".to_string()),
+        }
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Tile::Free => '.',
+            Tile::Blocked => '#',
+            Tile::Start => 'S',
+            Tile::End => 'E',
+        }
+    }
+}
+
 fn parse(input: &str) -> (Vec2i, Vec2i, Map<Tile>) {
-    let map = Map::<Tile>::from_lines(input.trim().lines(), &|c| match c {
-        '.' => Tile::Free,
-        '#' => Tile::Blocked,
-        'S' => Tile::Start,
-        'E' => Tile::End,
-        _ => unreachable!(),
-    });
+    let map: Map<Tile> = input.trim().parse().unwrap();
 
-    let start = map.iter().find(|(_, v)| **v == Tile::Start).unwrap().0;
-    let end = map.iter().find(|(_, v)| **v == Tile::End).unwrap().0;
+    let start = map.find_unique(&Tile::Start).unwrap();
+    let end = map.find_unique(&Tile::End).unwrap();
 
     (start, end, map)
 }
 
-fn bfs(start: Vec2i, end_tile: Tile, map: &Map<Tile>) -> Map<u32> {
-    let mut queue = VecDeque::<(Vec2i, u32)>::new(); // bfs queue to solve nominal problem
-    let mut costmap = map.same_size_with(u32::MAX); // keep track of distance to each visited node
-    queue.push_back((start, 0));
-    while let Some((pos, cost)) = queue.pop_front() {
-        if costmap[&pos] < u32::MAX {
-            continue;
-        }
-        costmap[&pos] = cost;
-        match &map[&pos] {
-            s if *s == end_tile => break,
-            Tile::Blocked => unreachable!(),
-            _ => {
-                for d in DIRECTIONS {
-                    let cand = pos.step(d, 1);
-                    if map[&cand] != Tile::Blocked {
-                        queue.push_back((cand, cost + 1));
-                    }
-                }
-            }
-        }
-    }
-    costmap
-}
-
 fn solve<const PAR: bool>(input: &str, cheat_duration: i32, cheat_count_limit: u32) -> Answer {
     let (start, end, map) = parse(input);
-    let costmap_fwd = bfs(start, Tile::End, &map);
-    let costmap_rev = bfs(end, Tile::Start, &map);
+    let passable = |t: &Tile| *t != Tile::Blocked;
+    let costmap_fwd = distance_field(&map, &[start], Metric::Bfs, passable).dist;
+    let costmap_rev = distance_field(&map, &[end], Metric::Bfs, passable).dist;
 
-    let nominal_cost = costmap_fwd[&end];
+    let nominal_cost = costmap_fwd[&end].unwrap();
 
-    let calc_cost_saving = |fwd_cost: u32, p: &Vec2i, (dx, dy): (i32, i32)| -> Option<u32> {
-        let p_skip = *p
-            + Vec2i {
-                x: dx as i64,
-                y: dy as i64,
-            };
+    let calc_cost_saving = |fwd_cost: u32, p: &Vec2i, d: Vec2<i32>| -> Option<u32> {
+        let p_skip = *p + Vec2i::from(d);
         let alternative_cost = fwd_cost
-            .checked_add(dx.unsigned_abs() + dy.unsigned_abs())?
-            .checked_add(*costmap_rev.get(&p_skip)?)?;
+            .checked_add(d.l1_norm() as u32)?
+            .checked_add((*costmap_rev.get(&p_skip)?)?)?;
         let savings = nominal_cost.checked_sub(alternative_cost)?;
         Some(savings)
     };
 
-    let mapper = |(p, fwd_cost): (Vec2i, &u32)| {
+    let mapper = |(p, fwd_cost): (Vec2i, u32)| {
         let mut cheat_count = 0;
         // iterate over all skip candidates within manhattan distance 'cheat_duration'
         for dx in -cheat_duration..=cheat_duration {
             let dy_max = cheat_duration - dx.abs();
             for dy in -dy_max..=dy_max {
-                if let Some(cost_savings) = calc_cost_saving(*fwd_cost, &p, (dx, dy)) {
+                if let Some(cost_savings) = calc_cost_saving(fwd_cost, &p, Vec2::new(dx, dy)) {
                     if cost_savings >= cheat_count_limit {
                         cheat_count += 1;
                     }
@@ -90,7 +77,7 @@ fn solve<const PAR: bool>(input: &str, cheat_duration: i32, cheat_count_limit: u
 
     let iterator = costmap_fwd
         .iter()
-        .filter(|(_, rev_cost)| **rev_cost < u32::MAX);
+        .filter_map(|(p, fwd_cost)| fwd_cost.map(|c| (p, c)));
     if PAR {
         Answer::Number(iterator.par_bridge().map(mapper).sum::<i64>())
     } else {
@@ -2,11 +2,9 @@ use crate::map2d::Map;
 use crate::Answer;
 
 fn count_cols(map: &Map<char>) -> Vec<u32> {
-    let mut ret = vec![0; map.w];
-    for (c, _) in map.iter().filter(|(_, v)| **v == '#') {
-        ret[c.x as usize] += 1;
-    }
-    ret
+    (0..map.w)
+        .map(|x| map.col(x).filter(|v| **v == '#').count() as u32)
+        .collect()
 }
 
 pub fn part_a(input: &str) -> Answer {
@@ -0,0 +1,336 @@
+use crate::container::RingBuffer;
+use crate::heap::MinHeap;
+use crate::map2d::Map;
+use crate::vec2::Vec2i;
+use bitvec::prelude::*;
+
+const INF: u32 = u32::MAX;
+
+/// Graph with unit edge weights and dense node indices.
+pub trait Graph {
+    /// Number of nodes.
+    fn num_nodes(&self) -> usize;
+
+    /// Neighbors of node n. Edges must be symmetric.
+    fn neighbors(&self, n: usize) -> impl Iterator<Item = usize> + '_;
+}
+
+/// Adjacency lists.
+impl Graph for Vec<Vec<usize>> {
+    fn num_nodes(&self) -> usize {
+        self.len()
+    }
+
+    fn neighbors(&self, n: usize) -> impl Iterator<Item = usize> + '_ {
+        self[n].iter().copied()
+    }
+}
+
+/// 4-connected h x w grid with row-major node indices.
+#[derive(Debug, Clone, Copy)]
+pub struct GridGraph {
+    pub h: usize,
+    pub w: usize,
+}
+
+impl GridGraph {
+    pub fn idx(&self, p: &Vec2i) -> usize {
+        assert!(p.is_in_grid(self.h, self.w));
+        p.linear_idx(self.w)
+    }
+}
+
+impl Graph for GridGraph {
+    fn num_nodes(&self) -> usize {
+        self.h * self.w
+    }
+
+    fn neighbors(&self, n: usize) -> impl Iterator<Item = usize> + '_ {
+        let (y, x) = (n / self.w, n % self.w);
+        [
+            (y > 0).then(|| n - self.w),
+            (x + 1 < self.w).then(|| n + 1),
+            (y + 1 < self.h).then(|| n + self.w),
+            (x > 0).then(|| n - 1),
+        ]
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// Single-source shortest path distances under node blocking and unblocking.
+///
+/// Blocking a node only invalidates the nodes whose shortest paths all pass
+/// through it, and re-settles those from their remaining neighbors. Unblocking
+/// propagates the distance decrease outwards from the node.
+#[derive(Debug, Clone)]
+pub struct DynamicSssp<G: Graph> {
+    graph: G,
+    source: usize,
+    blocked: BitVec,
+    dist: Vec<u32>,
+}
+
+impl<G: Graph> DynamicSssp<G> {
+    /// Create from a graph, a source node and initially blocked nodes.
+    pub fn new(graph: G, source: usize, blocked: BitVec) -> Self {
+        assert_eq!(blocked.len(), graph.num_nodes());
+        let mut ret = DynamicSssp {
+            dist: vec![INF; graph.num_nodes()],
+            graph,
+            source,
+            blocked,
+        };
+        if !ret.blocked[source] {
+            ret.dist[source] = 0;
+            ret.propagate(vec![source]);
+        }
+        ret
+    }
+
+    /// Distance from source, None if unreachable.
+    pub fn dist(&self, n: usize) -> Option<u32> {
+        (self.dist[n] != INF).then_some(self.dist[n])
+    }
+
+    /// Check if n can be reached from source.
+    pub fn is_reachable(&self, n: usize) -> bool {
+        self.dist[n] != INF
+    }
+
+    /// Check if n is blocked.
+    pub fn is_blocked(&self, n: usize) -> bool {
+        self.blocked[n]
+    }
+
+    /// Underlying graph.
+    pub fn graph(&self) -> &G {
+        &self.graph
+    }
+
+    /// Block node and repair distances.
+    pub fn block(&mut self, v: usize) {
+        if self.blocked.replace(v, true) || self.dist[v] == INF {
+            return;
+        }
+
+        // invalidate nodes that have no shortest-path parent left, in order of distance
+        let mut invalid = vec![v];
+        let mut queue = RingBuffer::new();
+        let d_v = self.dist[v];
+        self.dist[v] = INF;
+        queue.extend(self.graph.neighbors(v).filter(|w| self.dist[*w] == d_v + 1));
+        while let Some(u) = queue.pop_front() {
+            let d = self.dist[u];
+            if d == INF {
+                continue;
+            }
+            let has_parent = self
+                .graph
+                .neighbors(u)
+                .any(|p| self.dist[p] != INF && self.dist[p] + 1 == d);
+            if !has_parent {
+                self.dist[u] = INF;
+                invalid.push(u);
+                queue.extend(self.graph.neighbors(u).filter(|w| self.dist[*w] == d + 1));
+            }
+        }
+
+        // re-settle invalidated nodes from their valid neighbors
+        let mut seeds = Vec::new();
+        for u in invalid.into_iter().filter(|u| !self.blocked[*u]) {
+            let tentative = self.tentative(u);
+            if tentative != INF {
+                self.dist[u] = tentative;
+                seeds.push(u);
+            }
+        }
+        self.propagate(seeds);
+    }
+
+    /// Unblock node and repair distances.
+    pub fn unblock(&mut self, v: usize) {
+        if !self.blocked.replace(v, false) {
+            return;
+        }
+        let tentative = if v == self.source {
+            0
+        } else {
+            self.tentative(v)
+        };
+        if tentative != INF {
+            self.dist[v] = tentative;
+            self.propagate(vec![v]);
+        }
+    }
+
+    /// Best distance to n via its neighbors.
+    fn tentative(&self, n: usize) -> u32 {
+        self.graph
+            .neighbors(n)
+            .filter(|p| self.dist[*p] != INF)
+            .map(|p| self.dist[p] + 1)
+            .min()
+            .unwrap_or(INF)
+    }
+
+    /// Propagate distance decreases from seed nodes (with up-to-date distances).
+    fn propagate(&mut self, seeds: Vec<usize>) {
+        let mut heap = MinHeap::with_capacity(seeds.len(), |a: &(u32, usize), b: &(u32, usize)| {
+            a.0.cmp(&b.0)
+        });
+        for s in seeds {
+            heap.push((self.dist[s], s));
+        }
+        while let Some((d, u)) = heap.pop() {
+            if d != self.dist[u] {
+                continue; // stale entry
+            }
+            for w in self.graph.neighbors(u) {
+                if !self.blocked[w] && d + 1 < self.dist[w] {
+                    self.dist[w] = d + 1;
+                    heap.push((d + 1, w));
+                }
+            }
+        }
+    }
+}
+
+/// Grid convenience methods.
+impl DynamicSssp<GridGraph> {
+    /// Create from map, where cells that are not passable are blocked.
+    pub fn from_map<T, F>(map: &Map<T>, source: &Vec2i, passable: F) -> Self
+    where
+        F: Fn(&T) -> bool,
+    {
+        let graph = GridGraph { h: map.h, w: map.w };
+        let blocked = map.iter_values().map(|t| !passable(t)).collect();
+        Self::new(graph, graph.idx(source), blocked)
+    }
+
+    /// Block a cell.
+    pub fn block_cell(&mut self, p: &Vec2i) {
+        self.block(self.graph.idx(p));
+    }
+
+    /// Unblock a cell.
+    pub fn unblock_cell(&mut self, p: &Vec2i) {
+        self.unblock(self.graph.idx(p));
+    }
+
+    /// Distance to cell, None if unreachable.
+    pub fn cell_dist(&self, p: &Vec2i) -> Option<u32> {
+        self.dist(self.graph.idx(p))
+    }
+
+    /// Check if cell can be reached from the source.
+    pub fn is_cell_reachable(&self, p: &Vec2i) -> bool {
+        self.is_reachable(self.graph.idx(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    fn fresh_bfs<G: Graph>(graph: &G, source: usize, blocked: &BitVec) -> Vec<Option<u32>> {
+        let mut dist = vec![None; graph.num_nodes()];
+        let mut queue = RingBuffer::new();
+        if !blocked[source] {
+            dist[source] = Some(0);
+            queue.push_back(source);
+        }
+        while let Some(u) = queue.pop_front() {
+            for w in graph.neighbors(u) {
+                if !blocked[w] && dist[w].is_none() {
+                    dist[w] = Some(dist[u].unwrap() + 1);
+                    queue.push_back(w);
+                }
+            }
+        }
+        dist
+    }
+
+    fn check<G: Graph>(sssp: &DynamicSssp<G>) {
+        let expected = fresh_bfs(&sssp.graph, sssp.source, &sssp.blocked);
+        for (n, d) in expected.into_iter().enumerate() {
+            assert_eq!(sssp.dist(n), d, "node {n}");
+        }
+    }
+
+    #[test]
+    fn test_grid_random_updates() {
+        let map = Map::<bool>::new(12, 9);
+        let mut sssp = DynamicSssp::from_map(&map, &Vec2i::new(3, 4), |_| true);
+        check(&sssp);
+
+        let mut rng = Lcg::new(3);
+        for _ in 0..2000 {
+            let n = rng.below(map.h * map.w);
+            if rng.below(3) == 0 {
+                sssp.unblock(n);
+            } else {
+                sssp.block(n);
+            }
+            check(&sssp);
+        }
+    }
+
+    #[test]
+    fn test_graph_random_updates() {
+        // random sparse undirected graph
+        let mut rng = Lcg::new(11);
+        let n = 40;
+        let mut adj: Vec<Vec<usize>> = vec![vec![]; n];
+        for _ in 0..60 {
+            let (a, b) = (rng.below(n), rng.below(n));
+            if a != b && !adj[a].contains(&b) {
+                adj[a].push(b);
+                adj[b].push(a);
+            }
+        }
+        let mut sssp = DynamicSssp::new(adj, 0, bitvec![0; n]);
+        check(&sssp);
+        for _ in 0..1000 {
+            let v = rng.below(n);
+            if rng.below(2) == 0 {
+                sssp.unblock(v);
+            } else {
+                sssp.block(v);
+            }
+            check(&sssp);
+        }
+    }
+
+    #[test]
+    fn test_block_source() {
+        let map = Map::<bool>::new(3, 3);
+        let source = Vec2i::new(0, 0);
+        let mut sssp = DynamicSssp::from_map(&map, &source, |_| true);
+        assert_eq!(sssp.cell_dist(&Vec2i::new(2, 2)), Some(4));
+
+        sssp.block_cell(&source);
+        assert!(!sssp.is_cell_reachable(&Vec2i::new(2, 2)));
+        check(&sssp);
+
+        sssp.unblock_cell(&source);
+        assert_eq!(sssp.cell_dist(&Vec2i::new(2, 2)), Some(4));
+        check(&sssp);
+    }
+
+    #[test]
+    fn test_wall() {
+        let map: Map<char> = "...\n...\n...".parse().unwrap();
+        let mut sssp = DynamicSssp::from_map(&map, &Vec2i::new(0, 0), |c| *c == '.');
+        let end = Vec2i::new(2, 2);
+        sssp.block_cell(&Vec2i::new(1, 0));
+        sssp.block_cell(&Vec2i::new(1, 1));
+        assert_eq!(sssp.cell_dist(&end), Some(4));
+        sssp.block_cell(&Vec2i::new(1, 2));
+        assert!(!sssp.is_cell_reachable(&end));
+        sssp.unblock_cell(&Vec2i::new(1, 0));
+        assert_eq!(sssp.cell_dist(&end), Some(4));
+        check(&sssp);
+    }
+}
@@ -0,0 +1,24 @@
+//! Helpers shared by unit tests.
+
+/// Deterministic pseudo-random numbers for randomized tests (Knuth's MMIX LCG).
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    /// Advance and return the full state. The high bits are the most random.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    /// Pseudo-random value in [0, n).
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() >> 33) as usize % n
+    }
+}
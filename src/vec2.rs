@@ -1,4 +1,8 @@
-use std::ops::{Add, AddAssign, DivAssign, Mul, Neg, Sub};
+use std::fmt;
+use std::ops::{
+    Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign,
+};
+use std::str::FromStr;
 
 // 2D direction type
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
@@ -12,74 +16,380 @@ pub enum Dir {
 pub const DIRECTIONS: [Dir; 4] = [Dir::N, Dir::E, Dir::S, Dir::W];
 
 impl Dir {
+    /// All directions, clockwise from N (same order as Ord).
+    pub const fn all() -> [Dir; 4] {
+        DIRECTIONS
+    }
+
+    /// Parse from arrow (^>v<), compass (NESW) or screen (UDLR) character.
     pub fn from_char(c: char) -> Option<Self> {
         match c {
-            '^' => Some(Dir::N),
-            '>' => Some(Dir::E),
-            'v' => Some(Dir::S),
-            '<' => Some(Dir::W),
+            '^' | 'N' | 'U' => Some(Dir::N),
+            '>' | 'E' | 'R' => Some(Dir::E),
+            'v' | 'S' | 'D' => Some(Dir::S),
+            '<' | 'W' | 'L' => Some(Dir::W),
             _ => None,
         }
     }
 
-    pub fn turn_right(&self) -> Dir {
+    /// Arrow representation.
+    pub fn to_char(&self) -> char {
         match self {
-            Dir::N => Dir::E,
-            Dir::E => Dir::S,
-            Dir::S => Dir::W,
-            Dir::W => Dir::N,
+            Dir::N => '^',
+            Dir::E => '>',
+            Dir::S => 'v',
+            Dir::W => '<',
         }
     }
 
-    pub fn turn_left(&self) -> Dir {
+    /// Unit vector in "image" coordinates.
+    pub const fn to_vec(&self) -> Vec2i {
         match self {
-            Dir::N => Dir::W,
-            Dir::E => Dir::N,
-            Dir::S => Dir::E,
-            Dir::W => Dir::S,
+            Dir::N => Vec2i::new(0, -1),
+            Dir::E => Vec2i::new(1, 0),
+            Dir::S => Vec2i::new(0, 1),
+            Dir::W => Vec2i::new(-1, 0),
         }
     }
 
+    /// Direction of a unit vector.
+    pub fn from_vec(v: &Vec2i) -> Option<Self> {
+        DIRECTIONS.into_iter().find(|d| d.to_vec() == *v)
+    }
+
+    /// Index into per-direction arrays, consistent with Ord.
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Rotate k quarter turns clockwise (negative k turns counter-clockwise).
+    pub fn rotate(&self, k: i64) -> Dir {
+        DIRECTIONS[(*self as i64 + k).rem_euclid(4) as usize]
+    }
+
+    pub fn turn_right(&self) -> Dir {
+        self.rotate(1)
+    }
+
+    pub fn turn_left(&self) -> Dir {
+        self.rotate(-1)
+    }
+
     pub fn turn_around(&self) -> Dir {
+        self.rotate(2)
+    }
+}
+
+impl Neg for Dir {
+    type Output = Self;
+    fn neg(self) -> Self::Output {
+        self.turn_around()
+    }
+}
+
+// 2D direction type with diagonals
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
+pub enum Dir8 {
+    N = 0,
+    NE = 1,
+    E = 2,
+    SE = 3,
+    S = 4,
+    SW = 5,
+    W = 6,
+    NW = 7,
+}
+
+pub const DIRECTIONS8: [Dir8; 8] = [
+    Dir8::N,
+    Dir8::NE,
+    Dir8::E,
+    Dir8::SE,
+    Dir8::S,
+    Dir8::SW,
+    Dir8::W,
+    Dir8::NW,
+];
+
+impl Dir8 {
+    /// All directions, clockwise from N (same order as Ord).
+    pub const fn all() -> [Dir8; 8] {
+        DIRECTIONS8
+    }
+
+    /// Unit (or diagonal unit) vector in "image" coordinates.
+    pub const fn to_vec(&self) -> Vec2i {
         match self {
-            Dir::N => Dir::S,
-            Dir::E => Dir::W,
-            Dir::S => Dir::N,
-            Dir::W => Dir::E,
+            Dir8::N => Vec2i::new(0, -1),
+            Dir8::NE => Vec2i::new(1, -1),
+            Dir8::E => Vec2i::new(1, 0),
+            Dir8::SE => Vec2i::new(1, 1),
+            Dir8::S => Vec2i::new(0, 1),
+            Dir8::SW => Vec2i::new(-1, 1),
+            Dir8::W => Vec2i::new(-1, 0),
+            Dir8::NW => Vec2i::new(-1, -1),
         }
     }
+
+    /// Direction of a (diagonal) unit vector.
+    pub fn from_vec(v: &Vec2i) -> Option<Self> {
+        DIRECTIONS8.into_iter().find(|d| d.to_vec() == *v)
+    }
+
+    /// Index into per-direction arrays, consistent with Ord.
+    pub const fn index(&self) -> usize {
+        *self as usize
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        *self as usize % 2 == 1
+    }
+
+    /// Rotate k eighth turns (45 degrees) clockwise (negative k turns counter-clockwise).
+    pub fn rotate(&self, k: i64) -> Dir8 {
+        DIRECTIONS8[(*self as i64 + k).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(&self) -> Dir8 {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(&self) -> Dir8 {
+        self.rotate(-2)
+    }
+
+    pub fn turn_around(&self) -> Dir8 {
+        self.rotate(4)
+    }
 }
 
-impl Neg for Dir {
+impl From<Dir> for Dir8 {
+    fn from(d: Dir) -> Self {
+        DIRECTIONS8[2 * d as usize]
+    }
+}
+
+impl TryFrom<Dir8> for Dir {
+    type Error = Dir8;
+
+    fn try_from(d: Dir8) -> Result<Self, Self::Error> {
+        match d.is_diagonal() {
+            true => Err(d),
+            false => Ok(DIRECTIONS[d as usize / 2]),
+        }
+    }
+}
+
+/// Parse from compass name (N, NE, ...) or screen name (U, UR, ...), case-insensitive.
+impl FromStr for Dir8 {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "N" | "U" => Ok(Dir8::N),
+            "NE" | "UR" => Ok(Dir8::NE),
+            "E" | "R" => Ok(Dir8::E),
+            "SE" | "DR" => Ok(Dir8::SE),
+            "S" | "D" => Ok(Dir8::S),
+            "SW" | "DL" => Ok(Dir8::SW),
+            "W" | "L" => Ok(Dir8::W),
+            "NW" | "UL" => Ok(Dir8::NW),
+            _ => Err(format!("Invalid direction {s}")),
+        }
+    }
+}
+
+impl Neg for Dir8 {
     type Output = Self;
     fn neg(self) -> Self::Output {
         self.turn_around()
     }
 }
 
+/// Index per-direction arrays with Dir.
+impl<T> Index<Dir> for [T; 4] {
+    type Output = T;
+    fn index(&self, d: Dir) -> &T {
+        &self[d as usize]
+    }
+}
+
+impl<T> IndexMut<Dir> for [T; 4] {
+    fn index_mut(&mut self, d: Dir) -> &mut T {
+        &mut self[d as usize]
+    }
+}
+
+/// Index per-direction arrays with Dir8.
+impl<T> Index<Dir8> for [T; 8] {
+    type Output = T;
+    fn index(&self, d: Dir8) -> &T {
+        &self[d as usize]
+    }
+}
+
+impl<T> IndexMut<Dir8> for [T; 8] {
+    fn index_mut(&mut self, d: Dir8) -> &mut T {
+        &mut self[d as usize]
+    }
+}
+
+/// Numeric type that can be used as a vector component.
+pub trait Scalar:
+    Copy
+    + PartialEq
+    + PartialOrd
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + fmt::Display
+    + FromStr
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_scalar_signed {
+    ($($t:ty),+) => {$(
+        impl Scalar for $t {
+            const ZERO: Self = 0 as $t;
+            const ONE: Self = 1 as $t;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            fn signum(self) -> Self {
+                <$t>::signum(self)
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    )+};
+}
+
+macro_rules! impl_scalar_unsigned {
+    ($($t:ty),+) => {$(
+        impl Scalar for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+
+            fn abs(self) -> Self {
+                self
+            }
+
+            fn signum(self) -> Self {
+                (self > 0) as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+        }
+    )+};
+}
+
+impl_scalar_signed!(i32, i64, i128, isize, f32, f64);
+impl_scalar_unsigned!(u32, u64, usize);
+
 // 2D coordinate type
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd)]
-pub struct Vec2i {
-    pub x: i64,
-    pub y: i64,
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
 }
 
-impl Vec2i {
+// 3D coordinate type
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Ord, PartialOrd, Default)]
+pub struct Vec3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+/// Integer grid coordinate.
+pub type Vec2i = Vec2<i64>;
+
+/// Floating point 2D vector.
+pub type Vec2f = Vec2<f64>;
+
+/// Integer 3D coordinate.
+pub type Vec3i = Vec3<i64>;
+
+impl<T> Vec2<T> {
     #[inline]
-    pub const fn new(x: i64, y: i64) -> Self {
+    pub const fn new(x: T, y: T) -> Self {
         Self { x, y }
     }
+}
+
+impl<T> Vec3<T> {
+    #[inline]
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl<T: Scalar> Vec2<T> {
+    /// 2D cross product (z component of the 3D cross product).
+    pub fn cross(&self, o: &Self) -> T {
+        self.x * o.y - self.y * o.x
+    }
+
+    /// Rotate 90 degrees clockwise in "image" coordinates (E -> S).
+    pub fn rotate_cw(&self) -> Self
+    where
+        T: Neg<Output = T>,
+    {
+        Self::new(-self.y, self.x)
+    }
+
+    /// Rotate 90 degrees counter-clockwise in "image" coordinates (E -> N).
+    pub fn rotate_ccw(&self) -> Self
+    where
+        T: Neg<Output = T>,
+    {
+        Self::new(self.y, -self.x)
+    }
+}
+
+impl<T: Scalar> Vec3<T> {
+    pub fn cross(&self, o: &Self) -> Self {
+        Self::new(
+            self.y * o.z - self.z * o.y,
+            self.z * o.x - self.x * o.z,
+            self.x * o.y - self.y * o.x,
+        )
+    }
+}
+
+impl Vec2i {
+    /// Create from (y, x) indices, the order used by Map's tuple indexing.
+    pub fn from_yx((y, x): (usize, usize)) -> Self {
+        Self::new(x as i64, y as i64)
+    }
 
     /// Move in direction.
     /// This uses an "image" "x-east, y-south" coordinate system.
     pub fn step(&self, dir: Dir, d: i64) -> Self {
-        let (dx, dy) = match dir {
-            Dir::N => (0, -d),
-            Dir::E => (d, 0),
-            Dir::S => (0, d),
-            Dir::W => (-d, 0),
-        };
-        Self::new(self.x + dx, self.y + dy)
+        *self + dir.to_vec() * d
+    }
+
+    /// Move in direction, including diagonals.
+    pub fn step8(&self, dir: Dir8, d: i64) -> Self {
+        *self + dir.to_vec() * d
     }
 
     /// Check if (x,y) is contained in [0, w)x(0, h)
@@ -87,6 +397,11 @@ impl Vec2i {
         self.x >= 0 && self.x < w as i64 && self.y >= 0 && self.y < h as i64
     }
 
+    /// Wrap (x,y) into [0, w)x[0, h). Panics if h or w is zero.
+    pub fn wrap_in_grid(&self, h: usize, w: usize) -> Self {
+        Self::new(self.x.rem_euclid(w as i64), self.y.rem_euclid(h as i64))
+    }
+
     /// Get linear row-major index.
     pub fn linear_idx(&self, w: usize) -> usize {
         (self.y * w as i64 + self.x) as usize
@@ -103,44 +418,357 @@ impl Vec2i {
     }
 }
 
-impl Add<Vec2i> for Vec2i {
-    type Output = Self;
-    fn add(self, rhs: Vec2i) -> Self::Output {
-        Vec2i::new(self.x + rhs.x, self.y + rhs.y)
+/// Operations shared by Vec2 and Vec3.
+macro_rules! impl_vec {
+    ($V:ident, $n:literal, $($c:ident),+) => {
+        impl<T: Scalar> $V<T> {
+            /// Component-wise minimum.
+            pub fn component_min(self, o: Self) -> Self {
+                $V { $($c: if o.$c < self.$c { o.$c } else { self.$c }),+ }
+            }
+
+            /// Component-wise maximum.
+            pub fn component_max(self, o: Self) -> Self {
+                $V { $($c: if o.$c > self.$c { o.$c } else { self.$c }),+ }
+            }
+
+            /// Component-wise absolute value.
+            pub fn abs(self) -> Self {
+                $V { $($c: self.$c.abs()),+ }
+            }
+
+            /// Component-wise sign.
+            pub fn signum(self) -> Self {
+                $V { $($c: self.$c.signum()),+ }
+            }
+
+            pub fn dot(&self, o: &Self) -> T {
+                T::ZERO $(+ self.$c * o.$c)+
+            }
+
+            /// Sum of absolute components.
+            pub fn l1_norm(&self) -> T {
+                T::ZERO $(+ self.$c.abs())+
+            }
+
+            /// Largest absolute component.
+            pub fn chebyshev_norm(&self) -> T {
+                let mut ret = T::ZERO;
+                $(if self.$c.abs() > ret { ret = self.$c.abs(); })+
+                ret
+            }
+
+            pub fn euclidean_norm(&self) -> f64 {
+                self.dot(self).to_f64().sqrt()
+            }
+
+            /// Convert components losslessly.
+            pub fn cast<U: From<T>>(self) -> $V<U> {
+                $V { $($c: U::from(self.$c)),+ }
+            }
+
+            /// Convert components, failing if any does not fit.
+            pub fn try_cast<U: TryFrom<T>>(self) -> Result<$V<U>, U::Error> {
+                Ok($V { $($c: U::try_from(self.$c)?),+ })
+            }
+        }
+
+        impl<T> From<[T; $n]> for $V<T> {
+            fn from([$($c),+]: [T; $n]) -> Self {
+                $V { $($c),+ }
+            }
+        }
+
+        impl<T> From<$V<T>> for [T; $n] {
+            fn from(v: $V<T>) -> Self {
+                [$(v.$c),+]
+            }
+        }
+
+        impl<T: Scalar> Add for $V<T> {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self::Output {
+                $V { $($c: self.$c + rhs.$c),+ }
+            }
+        }
+
+        /// Add scalar to all components.
+        impl<T: Scalar> Add<T> for $V<T> {
+            type Output = Self;
+            fn add(self, rhs: T) -> Self::Output {
+                $V { $($c: self.$c + rhs),+ }
+            }
+        }
+
+        impl<T: Scalar> Sub for $V<T> {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self::Output {
+                $V { $($c: self.$c - rhs.$c),+ }
+            }
+        }
+
+        impl<T: Scalar> Mul<T> for $V<T> {
+            type Output = Self;
+            fn mul(self, rhs: T) -> Self::Output {
+                $V { $($c: self.$c * rhs),+ }
+            }
+        }
+
+        impl<T: Scalar> Div<T> for $V<T> {
+            type Output = Self;
+            fn div(self, rhs: T) -> Self::Output {
+                $V { $($c: self.$c / rhs),+ }
+            }
+        }
+
+        impl<T: Scalar + Neg<Output = T>> Neg for $V<T> {
+            type Output = Self;
+            fn neg(self) -> Self::Output {
+                $V { $($c: -self.$c),+ }
+            }
+        }
+
+        impl<T: Scalar> AddAssign for $V<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$c += rhs.$c;)+
+            }
+        }
+
+        impl<T: Scalar> AddAssign<&$V<T>> for $V<T> {
+            fn add_assign(&mut self, rhs: &Self) {
+                $(self.$c += rhs.$c;)+
+            }
+        }
+
+        impl<T: Scalar> SubAssign for $V<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$c -= rhs.$c;)+
+            }
+        }
+
+        impl<T: Scalar> MulAssign<T> for $V<T> {
+            fn mul_assign(&mut self, rhs: T) {
+                $(self.$c *= rhs;)+
+            }
+        }
+
+        impl<T: Scalar> DivAssign<T> for $V<T> {
+            fn div_assign(&mut self, rhs: T) {
+                $(self.$c /= rhs;)+
+            }
+        }
+
+        /// Comma-separated components.
+        impl<T: fmt::Display> fmt::Display for $V<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let parts = [$(self.$c.to_string()),+];
+                write!(f, "{}", parts.join(","))
+            }
+        }
+
+        /// Parse comma-separated components, ignoring whitespace.
+        impl<T: FromStr> FromStr for $V<T> {
+            type Err = String;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let mut it = s.split(',').map(str::trim);
+                $(
+                    let $c = it
+                        .next()
+                        .ok_or_else(|| format!("Too few components in {s:?}"))?
+                        .parse::<T>()
+                        .map_err(|_| format!("Invalid component in {s:?}"))?;
+                )+
+                match it.next() {
+                    Some(_) => Err(format!("Too many components in {s:?}")),
+                    None => Ok($V { $($c),+ }),
+                }
+            }
+        }
+    };
+}
+
+impl_vec!(Vec2, 2, x, y);
+impl_vec!(Vec3, 3, x, y, z);
+
+impl<T> From<(T, T)> for Vec2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Vec2 { x, y }
     }
 }
 
-impl Add<i64> for Vec2i {
-    type Output = Self;
-    fn add(self, rhs: i64) -> Self::Output {
-        Vec2i::new(self.x + rhs, self.y + rhs)
+impl<T> From<Vec2<T>> for (T, T) {
+    fn from(v: Vec2<T>) -> Self {
+        (v.x, v.y)
     }
 }
 
-impl Sub for Vec2i {
-    type Output = Self;
-    fn sub(self, rhs: Vec2i) -> Self::Output {
-        Vec2i::new(self.x - rhs.x, self.y - rhs.y)
+impl<T> From<(T, T, T)> for Vec3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Vec3 { x, y, z }
     }
 }
 
-impl Mul<i64> for Vec2i {
-    type Output = Self;
-    fn mul(self, rhs: i64) -> Self::Output {
-        Vec2i::new(self.x * rhs, self.y * rhs)
+impl<T> From<Vec3<T>> for (T, T, T) {
+    fn from(v: Vec3<T>) -> Self {
+        (v.x, v.y, v.z)
+    }
+}
+
+impl From<Vec2<i32>> for Vec2i {
+    fn from(v: Vec2<i32>) -> Self {
+        v.cast()
+    }
+}
+
+impl TryFrom<Vec2i> for Vec2<i32> {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(v: Vec2i) -> Result<Self, Self::Error> {
+        v.try_cast()
+    }
+}
+
+impl TryFrom<Vec2<usize>> for Vec2i {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(v: Vec2<usize>) -> Result<Self, Self::Error> {
+        v.try_cast()
     }
 }
 
-impl AddAssign<&Vec2i> for Vec2i {
-    fn add_assign(&mut self, rhs: &Vec2i) {
-        self.x += rhs.x;
-        self.y += rhs.y;
+impl TryFrom<Vec2i> for Vec2<usize> {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(v: Vec2i) -> Result<Self, Self::Error> {
+        v.try_cast()
     }
 }
 
-impl DivAssign<i64> for Vec2i {
-    fn div_assign(&mut self, rhs: i64) {
-        self.x /= rhs;
-        self.y /= rhs;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dir() {
+        for d in Dir::all() {
+            assert_eq!(Dir::from_vec(&d.to_vec()), Some(d));
+            assert_eq!(Dir::from_char(d.to_char()), Some(d));
+            assert_eq!(d.rotate(4), d);
+            assert_eq!(d.rotate(-1), d.turn_left());
+            assert_eq!(d.rotate(3), d.turn_left());
+            assert_eq!(d.to_vec() + (-d).to_vec(), Vec2i::new(0, 0));
+        }
+        assert_eq!(
+            "URDL".chars().flat_map(Dir::from_char).collect::<Vec<_>>(),
+            DIRECTIONS
+        );
+        assert_eq!(
+            "NESW".chars().flat_map(Dir::from_char).collect::<Vec<_>>(),
+            DIRECTIONS
+        );
+        assert_eq!(Dir::from_vec(&Vec2i::new(1, 1)), None);
+
+        let mut costs = [0; 4];
+        costs[Dir::S] = 3;
+        assert_eq!(costs[Dir::S.index()], 3);
+        assert!(Dir::all()
+            .windows(2)
+            .all(|w| w[0] < w[1] && w[0].index() < w[1].index()));
+    }
+
+    #[test]
+    fn test_dir8() {
+        let p = Vec2i::new(3, 3);
+        for d in Dir8::all() {
+            assert_eq!(Dir8::from_vec(&d.to_vec()), Some(d));
+            assert_eq!(d.rotate(8), d);
+            assert_eq!(d.rotate(1).rotate(-1), d);
+            assert_eq!(p.step8(d, 2).step8(-d, 2), p);
+            assert_eq!(d.is_diagonal(), d.to_vec().manhattan_norm() == 2);
+        }
+        for d in Dir::all() {
+            let d8 = Dir8::from(d);
+            assert_eq!(d8.to_vec(), d.to_vec());
+            assert_eq!(Dir::try_from(d8), Ok(d));
+        }
+        assert_eq!(Dir::try_from(Dir8::SW), Err(Dir8::SW));
+        assert_eq!(Dir8::N.rotate(3), Dir8::SE);
+        assert_eq!("ne".parse(), Ok(Dir8::NE));
+        assert_eq!("DL".parse(), Ok(Dir8::SW));
+        assert!("x".parse::<Dir8>().is_err());
+    }
+
+    #[test]
+    fn test_vec2_ops() {
+        let mut a = Vec2i::new(3, -4);
+        let b = Vec2i::new(-1, 2);
+        assert_eq!(a + b, Vec2i::new(2, -2));
+        assert_eq!(a - b, Vec2i::new(4, -6));
+        assert_eq!(-a, Vec2i::new(-3, 4));
+        assert_eq!(a * 2, Vec2i::new(6, -8));
+        assert_eq!(a / 2, Vec2i::new(1, -2));
+        assert_eq!(a + 1, Vec2i::new(4, -3));
+        assert_eq!(a.component_min(b), Vec2i::new(-1, -4));
+        assert_eq!(a.component_max(b), Vec2i::new(3, 2));
+        assert_eq!(a.max(b), a);
+        assert_eq!(a.abs(), Vec2i::new(3, 4));
+        assert_eq!(a.signum(), Vec2i::new(1, -1));
+        assert_eq!(a.dot(&b), -11);
+        assert_eq!(a.cross(&b), 2);
+        assert_eq!(a.l1_norm(), 7);
+        assert_eq!(a.chebyshev_norm(), 4);
+        assert_eq!(a.euclidean_norm(), 5.0);
+
+        a += b;
+        a -= Vec2i::new(1, 1);
+        a *= 3;
+        a /= 3;
+        assert_eq!(a, Vec2i::new(1, -3));
+
+        let e = Dir::E.to_vec();
+        assert_eq!(e.rotate_cw(), Dir::S.to_vec());
+        assert_eq!(e.rotate_ccw(), Dir::N.to_vec());
+        assert_eq!(b.rotate_cw().rotate_ccw(), b);
+
+        let f = Vec2f::new(0.5, -1.5);
+        assert_eq!(f * 2.0, Vec2f::new(1.0, -3.0));
+        assert_eq!(
+            f.abs().component_max(Vec2f::new(1.0, 1.0)),
+            Vec2f::new(1.0, 1.5)
+        );
+    }
+
+    #[test]
+    fn test_vec_conversions() {
+        let a: Vec2i = (1, 2).into();
+        assert_eq!(<(i64, i64)>::from(a), (1, 2));
+        assert_eq!(Vec2i::from([1, 2]), a);
+        assert_eq!(Vec2i::from_yx((2, 1)), a);
+        assert_eq!(Vec2i::from(Vec2::<i32>::new(1, 2)), a);
+        assert_eq!(Vec2::<i32>::try_from(a), Ok(Vec2::new(1, 2)));
+        assert!(Vec2::<usize>::try_from(-a).is_err());
+        assert!(Vec2::<i32>::try_from(Vec2i::new(1 << 40, 0)).is_err());
+        assert_eq!(Vec2::<i32>::new(1, 2).cast::<f64>(), Vec2f::new(1.0, 2.0));
+
+        assert_eq!(a.to_string(), "1,2");
+        assert_eq!("1, 2".parse(), Ok(a));
+        assert!("1".parse::<Vec2i>().is_err());
+        assert!("1,2,3".parse::<Vec2i>().is_err());
+        assert!("1,x".parse::<Vec2i>().is_err());
+    }
+
+    #[test]
+    fn test_vec3() {
+        let a = Vec3i::new(1, 0, 0);
+        let b = Vec3i::new(0, 1, 0);
+        assert_eq!(a.cross(&b), Vec3i::new(0, 0, 1));
+        assert_eq!(a.dot(&b), 0);
+        let c: Vec3i = "1,-2,3".parse().unwrap();
+        assert_eq!(c.to_string(), "1,-2,3");
+        assert_eq!(c.l1_norm(), 6);
+        assert_eq!(c.chebyshev_norm(), 3);
+        assert_eq!(c + a - b, Vec3i::new(2, -3, 3));
+        assert_eq!(Vec3i::from((1, -2, 3)), c);
     }
 }